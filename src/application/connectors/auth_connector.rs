@@ -1,15 +1,40 @@
 use async_trait::async_trait;
 use thiserror::Error;
+use time::OffsetDateTime;
 
 #[async_trait]
 pub trait AuthConnector: Send + Sync {
-    async fn create_session(&self, user_id: String, profile_id: String) -> Result<String, AuthConnectorError>;
+    async fn create_session(&self, user_id: String, profile_id: String, metadata: SessionMetadata) -> Result<String, AuthConnectorError>;
+    async fn revoke_session(&self, session_token: String) -> Result<(), AuthConnectorError>;
+    async fn revoke_session_by_id(&self, user_id: String, session_id: String) -> Result<(), AuthConnectorError>;
+    async fn revoke_all_sessions_for_user(&self, user_id: String) -> Result<(), AuthConnectorError>;
+    async fn list_sessions(&self, user_id: String, current_session_token: Option<String>) -> Result<Vec<SessionInfo>, AuthConnectorError>;
+}
+
+// Where a session was created from
+#[derive(Clone, Debug)]
+pub struct SessionMetadata {
+    pub ip_address: String,
+    pub device: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct SessionInfo {
+    pub id: String,
+    pub device: String,
+    pub ip_address: String,
+    pub created_at: OffsetDateTime,
+    pub last_seen_at: OffsetDateTime,
+    pub current: bool,
 }
 
 #[derive(Debug, Error)]
 pub enum AuthConnectorError {
     #[error(transparent)]
     UnexpectedError(anyhow::Error),
+
+    #[error("session-not-found")]
+    SessionNotFound,
 }
 
 #[cfg(test)]
@@ -17,11 +42,13 @@ mod mock {
     use std::sync::{Arc, Mutex};
 
     use async_trait::async_trait;
+    use time::OffsetDateTime;
     use uuid::Uuid;
 
-    use crate::application::connectors::auth_connector::{AuthConnector, AuthConnectorError};
+    use crate::application::connectors::auth_connector::{AuthConnector, AuthConnectorError, SessionInfo, SessionMetadata};
 
-    pub struct MockAuthConnector(Arc<Mutex<Vec<(String, String, String)>>>);
+    // (session token, user id, profile id, metadata)
+    pub struct MockAuthConnector(Arc<Mutex<Vec<(String, String, String, SessionMetadata)>>>);
 
     impl MockAuthConnector {
         pub fn new() -> Self {
@@ -33,13 +60,56 @@ mod mock {
 
     #[async_trait]
     impl AuthConnector for MockAuthConnector {
-        async fn create_session(&self, user_id: String, profile_id: String) -> Result<String, AuthConnectorError> {
+        async fn create_session(&self, user_id: String, profile_id: String, metadata: SessionMetadata) -> Result<String, AuthConnectorError> {
             let session_id = Uuid::new_v4().to_string();
 
             self.0.lock().unwrap()
-                .push((session_id.clone(), user_id.clone(), profile_id.clone()));
+                .push((session_id.clone(), user_id.clone(), profile_id.clone(), metadata));
 
             Ok(session_id)
         }
+
+        async fn revoke_session(&self, session_token: String) -> Result<(), AuthConnectorError> {
+            self.0.lock().unwrap()
+                .retain(|(token, _, _, _)| *token != session_token);
+
+            Ok(())
+        }
+
+        // The mock uses the token as the public id as well
+        async fn revoke_session_by_id(&self, user_id: String, session_id: String) -> Result<(), AuthConnectorError> {
+            let mut sessions = self.0.lock().unwrap();
+            let count = sessions.len();
+
+            sessions.retain(|(token, id, _, _)| !(*token == session_id && *id == user_id));
+
+            if sessions.len() == count {
+                return Err(AuthConnectorError::SessionNotFound);
+            }
+
+            Ok(())
+        }
+
+        async fn revoke_all_sessions_for_user(&self, user_id: String) -> Result<(), AuthConnectorError> {
+            self.0.lock().unwrap()
+                .retain(|(_, id, _, _)| *id != user_id);
+
+            Ok(())
+        }
+
+        async fn list_sessions(&self, user_id: String, current_session_token: Option<String>) -> Result<Vec<SessionInfo>, AuthConnectorError> {
+            Ok(self.0.lock().unwrap()
+                .iter()
+                .filter(|(_, id, _, _)| *id == user_id)
+                .map(|(token, _, _, metadata)| SessionInfo {
+                    id: token.clone(),
+                    device: metadata.device.clone(),
+                    ip_address: metadata.ip_address.clone(),
+                    created_at: OffsetDateTime::UNIX_EPOCH,
+                    last_seen_at: OffsetDateTime::UNIX_EPOCH,
+                    current: current_session_token.as_ref() == Some(token),
+                })
+                .collect())
+        }
     }
 }
\ No newline at end of file
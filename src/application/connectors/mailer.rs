@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+use thiserror::Error;
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: Mail) -> Result<(), MailerError>;
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug, Error)]
+pub enum MailerError {
+    #[error(transparent)]
+    UnexpectedError(anyhow::Error),
+}
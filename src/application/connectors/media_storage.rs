@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+use thiserror::Error;
+
+#[async_trait]
+pub trait MediaStorage: Send + Sync {
+    // Stores the object under the given key and returns its public url
+    async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> Result<String, MediaStorageError>;
+    async fn delete_by_prefix(&self, prefix: &str) -> Result<(), MediaStorageError>;
+    // None when the url wasn't handed out by this storage
+    fn key_from_url(&self, url: &str) -> Option<String>;
+}
+
+#[derive(Debug, Error)]
+pub enum MediaStorageError {
+    #[error(transparent)]
+    UnexpectedError(anyhow::Error),
+}
@@ -1 +1,3 @@
-pub mod auth_connector;
\ No newline at end of file
+pub mod auth_connector;
+pub mod mailer;
+pub mod media_storage;
@@ -7,6 +7,13 @@ use time::OffsetDateTime;
 #[strum_discriminants(derive(Hash))]
 pub enum DomainEvent {
     PasswordResetRequested(PasswordResetRequested),
+    UserEmailVerificationRequested(UserEmailVerificationRequested),
+    PasswordChanged(PasswordChanged),
+    EmailChangeRequested(EmailChangeRequested),
+    RecoveryCodeUsed(RecoveryCodeUsed),
+    UsernameChanged(UsernameChanged),
+    ProfileFollowed(ProfileFollowed),
+    ProfileUnfollowed(ProfileUnfollowed),
 }
 
 #[derive(Clone, Eq, Hash, PartialEq)]
@@ -36,4 +43,204 @@ impl From<PasswordResetRequested> for DomainEvent {
     fn from(value: PasswordResetRequested) -> Self {
         DomainEvent::PasswordResetRequested(value)
     }
+}
+
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct UserEmailVerificationRequested {
+    pub token: String,
+    pub email: String,
+    pub datetime: OffsetDateTime
+}
+
+#[async_trait]
+impl<S: StateTrait> FromContext<DomainEventDiscriminants, DomainEvent, S> for UserEmailVerificationRequested
+{
+    async fn from_context(ctx: &Context<DomainEvent, S>) -> Self {
+        match &ctx.event {
+            DomainEvent::UserEmailVerificationRequested(event) => event.clone(),
+            _ => unreachable!()
+        }
+    }
+
+    fn topic() -> Option<DomainEventDiscriminants> {
+        Some(DomainEventDiscriminants::UserEmailVerificationRequested)
+    }
+}
+
+impl From<UserEmailVerificationRequested> for DomainEvent {
+    fn from(value: UserEmailVerificationRequested) -> Self {
+        DomainEvent::UserEmailVerificationRequested(value)
+    }
+}
+
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct PasswordChanged {
+    pub user_id: String,
+    pub email: String,
+    pub datetime: OffsetDateTime
+}
+
+#[async_trait]
+impl<S: StateTrait> FromContext<DomainEventDiscriminants, DomainEvent, S> for PasswordChanged
+{
+    async fn from_context(ctx: &Context<DomainEvent, S>) -> Self {
+        match &ctx.event {
+            DomainEvent::PasswordChanged(event) => event.clone(),
+            _ => unreachable!()
+        }
+    }
+
+    fn topic() -> Option<DomainEventDiscriminants> {
+        Some(DomainEventDiscriminants::PasswordChanged)
+    }
+}
+
+impl From<PasswordChanged> for DomainEvent {
+    fn from(value: PasswordChanged) -> Self {
+        DomainEvent::PasswordChanged(value)
+    }
+}
+
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct EmailChangeRequested {
+    pub user_id: String,
+    pub old_email: String,
+    pub new_email: String,
+    pub token: String,
+    pub datetime: OffsetDateTime
+}
+
+#[async_trait]
+impl<S: StateTrait> FromContext<DomainEventDiscriminants, DomainEvent, S> for EmailChangeRequested
+{
+    async fn from_context(ctx: &Context<DomainEvent, S>) -> Self {
+        match &ctx.event {
+            DomainEvent::EmailChangeRequested(event) => event.clone(),
+            _ => unreachable!()
+        }
+    }
+
+    fn topic() -> Option<DomainEventDiscriminants> {
+        Some(DomainEventDiscriminants::EmailChangeRequested)
+    }
+}
+
+impl From<EmailChangeRequested> for DomainEvent {
+    fn from(value: EmailChangeRequested) -> Self {
+        DomainEvent::EmailChangeRequested(value)
+    }
+}
+
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct RecoveryCodeUsed {
+    pub user_id: String,
+    pub email: String,
+    pub remaining_codes: usize,
+    pub datetime: OffsetDateTime
+}
+
+#[async_trait]
+impl<S: StateTrait> FromContext<DomainEventDiscriminants, DomainEvent, S> for RecoveryCodeUsed
+{
+    async fn from_context(ctx: &Context<DomainEvent, S>) -> Self {
+        match &ctx.event {
+            DomainEvent::RecoveryCodeUsed(event) => event.clone(),
+            _ => unreachable!()
+        }
+    }
+
+    fn topic() -> Option<DomainEventDiscriminants> {
+        Some(DomainEventDiscriminants::RecoveryCodeUsed)
+    }
+}
+
+impl From<RecoveryCodeUsed> for DomainEvent {
+    fn from(value: RecoveryCodeUsed) -> Self {
+        DomainEvent::RecoveryCodeUsed(value)
+    }
+}
+
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct UsernameChanged {
+    pub profile_id: String,
+    pub old_username: String,
+    pub new_username: String,
+    pub datetime: OffsetDateTime
+}
+
+#[async_trait]
+impl<S: StateTrait> FromContext<DomainEventDiscriminants, DomainEvent, S> for UsernameChanged
+{
+    async fn from_context(ctx: &Context<DomainEvent, S>) -> Self {
+        match &ctx.event {
+            DomainEvent::UsernameChanged(event) => event.clone(),
+            _ => unreachable!()
+        }
+    }
+
+    fn topic() -> Option<DomainEventDiscriminants> {
+        Some(DomainEventDiscriminants::UsernameChanged)
+    }
+}
+
+impl From<UsernameChanged> for DomainEvent {
+    fn from(value: UsernameChanged) -> Self {
+        DomainEvent::UsernameChanged(value)
+    }
+}
+
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct ProfileFollowed {
+    pub follower_id: String,
+    pub followee_id: String,
+    pub datetime: OffsetDateTime
+}
+
+#[async_trait]
+impl<S: StateTrait> FromContext<DomainEventDiscriminants, DomainEvent, S> for ProfileFollowed
+{
+    async fn from_context(ctx: &Context<DomainEvent, S>) -> Self {
+        match &ctx.event {
+            DomainEvent::ProfileFollowed(event) => event.clone(),
+            _ => unreachable!()
+        }
+    }
+
+    fn topic() -> Option<DomainEventDiscriminants> {
+        Some(DomainEventDiscriminants::ProfileFollowed)
+    }
+}
+
+impl From<ProfileFollowed> for DomainEvent {
+    fn from(value: ProfileFollowed) -> Self {
+        DomainEvent::ProfileFollowed(value)
+    }
+}
+
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct ProfileUnfollowed {
+    pub follower_id: String,
+    pub followee_id: String,
+    pub datetime: OffsetDateTime
+}
+
+#[async_trait]
+impl<S: StateTrait> FromContext<DomainEventDiscriminants, DomainEvent, S> for ProfileUnfollowed
+{
+    async fn from_context(ctx: &Context<DomainEvent, S>) -> Self {
+        match &ctx.event {
+            DomainEvent::ProfileUnfollowed(event) => event.clone(),
+            _ => unreachable!()
+        }
+    }
+
+    fn topic() -> Option<DomainEventDiscriminants> {
+        Some(DomainEventDiscriminants::ProfileUnfollowed)
+    }
+}
+
+impl From<ProfileUnfollowed> for DomainEvent {
+    fn from(value: ProfileUnfollowed) -> Self {
+        DomainEvent::ProfileUnfollowed(value)
+    }
 }
\ No newline at end of file
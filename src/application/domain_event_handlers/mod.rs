@@ -1 +1,2 @@
-pub mod user_created;
\ No newline at end of file
+pub mod profile_events;
+pub mod user_created;
@@ -0,0 +1,22 @@
+use figure_lib::queue::internal_event_router::RouterError;
+use tracing::info;
+
+use crate::application::domain_event_dispatcher::{ProfileFollowed, ProfileUnfollowed, UsernameChanged};
+
+pub async fn username_changed(event: UsernameChanged) -> Result<(), RouterError> {
+    info!("Profile {} changed username from {} to {}", event.profile_id, event.old_username, event.new_username);
+
+    Ok(())
+}
+
+pub async fn profile_followed(event: ProfileFollowed) -> Result<(), RouterError> {
+    info!("Profile {} followed {}", event.follower_id, event.followee_id);
+
+    Ok(())
+}
+
+pub async fn profile_unfollowed(event: ProfileUnfollowed) -> Result<(), RouterError> {
+    info!("Profile {} unfollowed {}", event.follower_id, event.followee_id);
+
+    Ok(())
+}
@@ -1,13 +1,104 @@
 use std::sync::Arc;
 
 use figure_lib::queue::internal_event_router::{RouterError, State};
-use tracing::info;
+use tracing::{error, info};
 
-use crate::application::domain_event_dispatcher::PasswordResetRequested;
+use crate::application::connectors::mailer::Mailer;
+use crate::application::domain_event_dispatcher::{EmailChangeRequested, PasswordChanged, PasswordResetRequested, RecoveryCodeUsed, UserEmailVerificationRequested};
+use crate::application::mail_templates;
 use crate::application::state::DomainEventHandlerState;
 
 // todo save to some kind of audit table/system
 pub async fn password_reset_requested(State(state): State<Arc<DomainEventHandlerState>>, event: PasswordResetRequested) -> Result<(), RouterError> {
     info!("{}", event.requester);
+
+    send_password_reset_mail(state.mailer.as_ref(), &state.origin, &event).await;
+
+    Ok(())
+}
+
+// Delivery failures are only logged, a broken mail relay shouldn't roll back the reset request
+async fn send_password_reset_mail(mailer: &dyn Mailer, origin: &str, event: &PasswordResetRequested) {
+    let mail = mail_templates::password_reset(origin, &event.email, &event.token);
+
+    if let Err(e) = mailer.send(mail).await {
+        error!("Failed to send password reset mail: {}", e);
+    }
+}
+
+pub async fn user_email_verification_requested(State(state): State<Arc<DomainEventHandlerState>>, event: UserEmailVerificationRequested) -> Result<(), RouterError> {
+    let mail = mail_templates::email_verification(&state.origin, &event.email, &event.token);
+
+    if let Err(e) = state.mailer.send(mail).await {
+        error!("Failed to send email verification mail: {}", e);
+    }
+
+    Ok(())
+}
+
+pub async fn password_changed(State(state): State<Arc<DomainEventHandlerState>>, event: PasswordChanged) -> Result<(), RouterError> {
+    info!("Password changed for user {}", event.user_id);
+
+    if let Err(e) = state.mailer.send(mail_templates::password_changed(&event.email)).await {
+        error!("Failed to send password changed mail: {}", e);
+    }
+
+    Ok(())
+}
+
+pub async fn email_change_requested(State(state): State<Arc<DomainEventHandlerState>>, event: EmailChangeRequested) -> Result<(), RouterError> {
+    info!("Email change requested for user {}", event.user_id);
+
+    let confirmation = mail_templates::email_change_confirmation(&state.origin, &event.new_email, &event.token);
+
+    if let Err(e) = state.mailer.send(confirmation).await {
+        error!("Failed to send email change confirmation mail: {}", e);
+    }
+
+    let notice = mail_templates::email_change_notice(&event.old_email, &event.new_email);
+
+    if let Err(e) = state.mailer.send(notice).await {
+        error!("Failed to send email change notice mail: {}", e);
+    }
+
     Ok(())
-}
\ No newline at end of file
+}
+
+pub async fn recovery_code_used(State(state): State<Arc<DomainEventHandlerState>>, event: RecoveryCodeUsed) -> Result<(), RouterError> {
+    info!("Recovery code used by user {}, {} left", event.user_id, event.remaining_codes);
+
+    let mail = mail_templates::recovery_code_used(&event.email, event.remaining_codes);
+
+    if let Err(e) = state.mailer.send(mail).await {
+        error!("Failed to send recovery code used mail: {}", e);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use crate::infrastructure::InMemoryMailer;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn password_reset_mail_contains_the_reset_link() {
+        let mailer = InMemoryMailer::new();
+        let event = PasswordResetRequested {
+            token: "reset-token".to_string(),
+            email: "bob@example.com".to_string(),
+            requester: "127.0.0.1".to_string(),
+            datetime: OffsetDateTime::now_utc(),
+        };
+
+        send_password_reset_mail(&mailer, "https://example.com", &event).await;
+
+        let sent_mails = mailer.sent_mails();
+        assert_eq!(sent_mails.len(), 1);
+        assert_eq!(sent_mails[0].to, "bob@example.com");
+        assert!(sent_mails[0].body.contains("https://example.com/reset-password?token=reset-token"));
+    }
+}
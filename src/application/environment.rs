@@ -1,10 +1,17 @@
 use std::env;
 use std::env::VarError;
+use std::net::SocketAddr;
 
 use tracing::log::{error, warn};
 
 use crate::application::errors::ApplicationError;
 
+const DEFAULT_RESERVED_USERNAMES: [&str; 24] = [
+    "about", "admin", "administrator", "api", "help", "login", "logout", "me",
+    "moderator", "official", "privacy", "profile", "profiles", "root", "security", "settings",
+    "signin", "signout", "signup", "staff", "support", "system", "terms", "user",
+];
+
 #[derive(Clone)]
 pub struct Environment {
     pub database_url: String,
@@ -13,9 +20,55 @@ pub struct Environment {
     pub origin: String,
 
     pub server_port: u16,
+    // Listener for routes only other services may call, keep it off the public network
+    pub internal_server_address: SocketAddr,
 
     pub auth_host: String,
     pub auth_port: u16,
+
+    // Optional, rate limiting falls back to memory without it
+    pub redis_url: Option<String>,
+
+    // Secret shared with the gateway to sign session headers
+    pub session_header_secret: Option<String>,
+    // Local development only, accepts unsigned session headers
+    pub trust_session_headers: bool,
+
+    // Mail transport: smtp, file or memory
+    pub mail_transport: String,
+    pub mail_from: String,
+    pub mail_directory: String,
+    pub smtp_host: Option<String>,
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+
+    // Reject unverified accounts
+    pub verified_email_required_for_sign_in: bool,
+    pub verified_email_required_for_profile_update: bool,
+
+    // Passphrase used to encrypt TOTP secrets at rest
+    pub totp_encryption_key: String,
+
+    // Failed sign-ins before an account gets locked and the initial lock duration
+    pub sign_in_lockout_threshold: i32,
+    pub sign_in_lockout_seconds: u64,
+
+    // Comma separated, replaces the default list when set
+    pub reserved_usernames: Vec<String>,
+    // File with one blocked substring per line, lines starting with # are ignored
+    pub username_blocklist_file: Option<String>,
+
+    // Media storage: local or s3, and the base url objects are served from
+    pub media_storage: String,
+    pub media_public_url: String,
+    pub media_directory: String,
+    pub s3_bucket: Option<String>,
+    pub s3_region: Option<String>,
+    // Only needed for S3 compatible services other than AWS
+    pub s3_endpoint: Option<String>,
+    pub s3_access_key_id: Option<String>,
+    pub s3_secret_access_key: Option<String>,
 }
 
 impl Environment {
@@ -36,8 +89,54 @@ impl Environment {
                     warn!("{error_reason}, defaulting to port 8000");
                     "8000".to_string()
                 }).parse::<u16>().expect("Invalid SERVER_PORT env"),
+                internal_server_address: get_var("INTERNAL_SERVER_ADDRESS")
+                    .unwrap_or_else(|_| "127.0.0.1:8002".to_string())
+                    .parse().expect("Invalid INTERNAL_SERVER_ADDRESS env"),
                 auth_host: get_var("AUTH_HOST").expect("No AUTH_HOST env found"),
                 auth_port: get_var("AUTH_PORT").expect("No AUTH_PORT env found").parse().unwrap(),
+                redis_url: get_var("REDIS_URL").ok(),
+                session_header_secret: get_var("SESSION_HEADER_SECRET").ok(),
+                trust_session_headers: get_var("TRUST_SESSION_HEADERS")
+                    .map(|value| value.parse().expect("Invalid TRUST_SESSION_HEADERS env"))
+                    .unwrap_or(false),
+                mail_transport: get_var("MAIL_TRANSPORT").unwrap_or_else(|_| {
+                    warn!("Environment variable MAIL_TRANSPORT not found, defaulting to file");
+                    "file".to_string()
+                }),
+                mail_from: get_var("MAIL_FROM").unwrap_or_else(|_| "no-reply@localhost".to_string()),
+                mail_directory: get_var("MAIL_DIRECTORY").unwrap_or_else(|_| "./mails".to_string()),
+                smtp_host: get_var("SMTP_HOST").ok(),
+                smtp_port: get_var("SMTP_PORT").ok().map(|port| port.parse().expect("Invalid SMTP_PORT env")),
+                smtp_username: get_var("SMTP_USERNAME").ok(),
+                smtp_password: get_var("SMTP_PASSWORD").ok(),
+                verified_email_required_for_sign_in: get_var("REQUIRE_VERIFIED_EMAIL_FOR_SIGN_IN")
+                    .map(|value| value.parse().expect("Invalid REQUIRE_VERIFIED_EMAIL_FOR_SIGN_IN env"))
+                    .unwrap_or(false),
+                verified_email_required_for_profile_update: get_var("REQUIRE_VERIFIED_EMAIL_FOR_PROFILE_UPDATE")
+                    .map(|value| value.parse().expect("Invalid REQUIRE_VERIFIED_EMAIL_FOR_PROFILE_UPDATE env"))
+                    .unwrap_or(false),
+                totp_encryption_key: get_var("TOTP_ENCRYPTION_KEY").expect("No TOTP_ENCRYPTION_KEY env found"),
+                sign_in_lockout_threshold: get_var("SIGN_IN_LOCKOUT_THRESHOLD")
+                    .map(|value| value.parse().expect("Invalid SIGN_IN_LOCKOUT_THRESHOLD env"))
+                    .unwrap_or(5),
+                sign_in_lockout_seconds: get_var("SIGN_IN_LOCKOUT_SECONDS")
+                    .map(|value| value.parse().expect("Invalid SIGN_IN_LOCKOUT_SECONDS env"))
+                    .unwrap_or(60),
+                reserved_usernames: get_var("RESERVED_USERNAMES")
+                    .map(|value| value.split(',').map(|username| username.trim().to_string()).collect())
+                    .unwrap_or_else(|_| DEFAULT_RESERVED_USERNAMES.iter().map(|username| username.to_string()).collect()),
+                username_blocklist_file: get_var("USERNAME_BLOCKLIST_FILE").ok(),
+                media_storage: get_var("MEDIA_STORAGE").unwrap_or_else(|_| {
+                    warn!("Environment variable MEDIA_STORAGE not found, defaulting to local");
+                    "local".to_string()
+                }),
+                media_public_url: get_var("MEDIA_PUBLIC_URL").unwrap_or_else(|_| "/media".to_string()),
+                media_directory: get_var("MEDIA_DIRECTORY").unwrap_or_else(|_| "./media".to_string()),
+                s3_bucket: get_var("S3_BUCKET").ok(),
+                s3_region: get_var("S3_REGION").ok(),
+                s3_endpoint: get_var("S3_ENDPOINT").ok(),
+                s3_access_key_id: get_var("S3_ACCESS_KEY_ID").ok(),
+                s3_secret_access_key: get_var("S3_SECRET_ACCESS_KEY").ok(),
             }
         )
     }
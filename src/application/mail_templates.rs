@@ -0,0 +1,76 @@
+use crate::application::connectors::mailer::Mail;
+
+pub fn password_reset(origin: &str, email: &str, token: &str) -> Mail {
+    Mail {
+        to: email.to_string(),
+        subject: "Reset your password".to_string(),
+        body: format!(
+            "Hi,\n\n\
+            Someone requested a password reset for your account.\n\
+            You can choose a new password using the link below, it is valid for one hour:\n\n\
+            {origin}/reset-password?token={token}\n\n\
+            If you did not request this, you can safely ignore this email."
+        ),
+    }
+}
+
+pub fn email_verification(origin: &str, email: &str, token: &str) -> Mail {
+    Mail {
+        to: email.to_string(),
+        subject: "Verify your email address".to_string(),
+        body: format!(
+            "Hi,\n\n\
+            Please confirm this email address using the link below, it is valid for 24 hours:\n\n\
+            {origin}/verify-email?token={token}\n\n\
+            If you did not create an account, you can safely ignore this email."
+        ),
+    }
+}
+
+pub fn password_changed(email: &str) -> Mail {
+    Mail {
+        to: email.to_string(),
+        subject: "Your password was changed".to_string(),
+        body: "Hi,\n\n\
+            The password of your account was just changed.\n\
+            If this wasn't you, reset your password right away.".to_string(),
+    }
+}
+
+pub fn email_change_confirmation(origin: &str, new_email: &str, token: &str) -> Mail {
+    Mail {
+        to: new_email.to_string(),
+        subject: "Confirm your new email address".to_string(),
+        body: format!(
+            "Hi,\n\n\
+            Please confirm that you want to use this address for your account, the link is valid for 24 hours:\n\n\
+            {origin}/confirm-email-change?token={token}\n\n\
+            If you did not request this, you can safely ignore this email."
+        ),
+    }
+}
+
+pub fn email_change_notice(old_email: &str, new_email: &str) -> Mail {
+    Mail {
+        to: old_email.to_string(),
+        subject: "Your email address is about to change".to_string(),
+        body: format!(
+            "Hi,\n\n\
+            Someone requested to change the email address of your account to {new_email}.\n\
+            The change only takes effect once it is confirmed from the new address.\n\
+            If this wasn't you, change your password right away."
+        ),
+    }
+}
+
+pub fn recovery_code_used(email: &str, remaining_codes: usize) -> Mail {
+    Mail {
+        to: email.to_string(),
+        subject: "A recovery code was used to sign in".to_string(),
+        body: format!(
+            "Hi,\n\n\
+            One of your recovery codes was just used to sign in to your account, {remaining_codes} codes are left.\n\
+            If this wasn't you, change your password and regenerate your recovery codes right away."
+        ),
+    }
+}
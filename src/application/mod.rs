@@ -9,3 +9,5 @@ pub mod domain_event_dispatcher;
 pub mod domain_event_handlers;
 pub mod state;
 pub mod environment;
+pub mod mail_templates;
+pub mod pagination;
@@ -0,0 +1,48 @@
+use time::OffsetDateTime;
+
+// Cursors are opaque to clients, they only hand back what they received as next_cursor
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+pub const MAX_PAGE_SIZE: i64 = 50;
+
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+// Position in a list of profiles ordered by when they were added (followed, blocked, muted)
+// and then by profile id, both descending
+#[derive(Clone, Debug)]
+pub struct DatedCursor {
+    pub created_at: OffsetDateTime,
+    pub profile_id: String,
+}
+
+impl DatedCursor {
+    pub fn encode(&self) -> String {
+        encode_cursor(&[&self.created_at.unix_timestamp_nanos().to_string(), &self.profile_id])
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        match decode_cursor(cursor)?.as_slice() {
+            [created_at, profile_id] => Some(Self {
+                created_at: OffsetDateTime::from_unix_timestamp_nanos(created_at.parse().ok()?).ok()?,
+                profile_id: profile_id.clone(),
+            }),
+            _ => None
+        }
+    }
+}
+
+pub fn page_size(limit: Option<i64>) -> i64 {
+    limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+pub fn encode_cursor(parts: &[&str]) -> String {
+    hex::encode(parts.join("\n"))
+}
+
+pub fn decode_cursor(cursor: &str) -> Option<Vec<String>> {
+    let decoded = String::from_utf8(hex::decode(cursor).ok()?).ok()?;
+
+    Some(decoded.split('\n').map(|part| part.to_string()).collect())
+}
@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+use crate::application::errors::RepositoryError;
+use crate::application::pagination::DatedCursor;
+use crate::domain::Profile;
+
+// Blocks and mutes owned by a profile, listed most recent first
+#[async_trait]
+pub trait BlockRepository: Send + Sync {
+    async fn insert_block(&self, blocker_id: &str, blocked_id: &str) -> Result<(), RepositoryError>;
+    async fn delete_block(&self, blocker_id: &str, blocked_id: &str) -> Result<(), RepositoryError>;
+    async fn is_blocked(&self, blocker_id: &str, blocked_id: &str) -> Result<bool, RepositoryError>;
+    // Which of the given profiles blocked this one
+    async fn find_blocker_ids(&self, blocked_id: &str, profile_ids: &[String]) -> Result<Vec<String>, RepositoryError>;
+    async fn find_blocked(&self, blocker_id: &str, after: Option<DatedCursor>, limit: i64) -> Result<Vec<(Profile, DatedCursor)>, RepositoryError>;
+    async fn insert_mute(&self, muter_id: &str, muted_id: &str) -> Result<(), RepositoryError>;
+    async fn delete_mute(&self, muter_id: &str, muted_id: &str) -> Result<(), RepositoryError>;
+    async fn find_muted(&self, muter_id: &str, after: Option<DatedCursor>, limit: i64) -> Result<Vec<(Profile, DatedCursor)>, RepositoryError>;
+}
@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+
+use crate::application::errors::RepositoryError;
+use crate::application::pagination::DatedCursor;
+use crate::domain::{Follow, Profile};
+
+#[async_trait]
+pub trait FollowRepository: Send + Sync {
+    // Also updates the follower and following counts of both profiles
+    async fn insert(&self, follow: &Follow) -> Result<(), RepositoryError>;
+    async fn find(&self, follower_id: &str, followee_id: &str) -> Result<Follow, RepositoryError>;
+    async fn delete(&self, follow: &Follow) -> Result<(), RepositoryError>;
+    async fn find_followers(&self, profile_id: &str, after: Option<DatedCursor>, limit: i64) -> Result<Vec<(Profile, DatedCursor)>, RepositoryError>;
+    async fn find_following(&self, profile_id: &str, after: Option<DatedCursor>, limit: i64) -> Result<Vec<(Profile, DatedCursor)>, RepositoryError>;
+    // Which of the given profiles the follower follows
+    async fn find_followed_ids(&self, follower_id: &str, profile_ids: &[String]) -> Result<Vec<String>, RepositoryError>;
+}
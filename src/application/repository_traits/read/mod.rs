@@ -1,2 +1,4 @@
+pub mod block_repository;
+pub mod follow_repository;
 pub mod profile_repository;
 pub mod user_repository;
\ No newline at end of file
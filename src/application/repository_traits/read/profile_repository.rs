@@ -1,13 +1,59 @@
 use async_trait::async_trait;
+use time::OffsetDateTime;
 
 use crate::application::errors::RepositoryError;
-use crate::domain::Profile;
+use crate::domain::{Profile, Visibility};
+
+// Position of a search result, results are ordered by rank and then id, both descending
+#[derive(Clone, Debug)]
+pub struct SearchCursor {
+    pub rank: f32,
+    pub id: String,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProfileVisibility {
+    pub profile: Option<Visibility>,
+    pub display_name: Option<Visibility>,
+    pub bio: Option<Visibility>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProfileSort {
+    // Most recently created first
+    Newest,
+    Username,
+}
+
+// Position of the last profile of a page, matching the sort it was listed with
+#[derive(Clone, Debug)]
+pub enum ListCursor {
+    Newest { created_at: OffsetDateTime, id: String },
+    Username { username: String },
+}
 
 #[async_trait]
 pub trait ProfileRepository: Send + Sync {
     async fn insert(&self, profile: &Profile) -> Result<(), RepositoryError>;
     async fn find_by_id(&self, profile_id: String) -> Result<Profile, RepositoryError>;
+    // Case-insensitive
+    async fn find_by_username(&self, username: &str) -> Result<Profile, RepositoryError>;
+    async fn find_by_username_skeleton(&self, skeleton: &str) -> Result<Profile, RepositoryError>;
+    // Ids that don't exist are left out, the order isn't guaranteed
+    async fn find_by_ids(&self, profile_ids: &[String]) -> Result<Vec<Profile>, RepositoryError>;
     async fn find_by_user_id(&self, user_id: String) -> Result<Profile, RepositoryError>;
-    async fn update_profile_by_id(&self, profile_id: String, display_name: Option<String>, bio: Option<String>) -> Result<(), RepositoryError>;
+    // A display name or bio that is None is cleared, visibility settings that are None stay unchanged
+    async fn update_profile_by_id(&self, profile_id: String, display_name: Option<String>, bio: Option<String>, visibility: ProfileVisibility) -> Result<(), RepositoryError>;
+    // Saves the new username and keeps the old one in the username history
+    async fn change_username(&self, profile: &Profile, old_username: &str) -> Result<(), RepositoryError>;
+    // Most recent profile that used this username since the given time, case-insensitive
+    async fn find_by_previous_username(&self, username: &str, changed_after: OffsetDateTime) -> Result<Profile, RepositoryError>;
+    async fn find_by_previous_username_skeleton(&self, skeleton: &str, changed_after: OffsetDateTime) -> Result<Profile, RepositoryError>;
+    async fn update_profile_picture_by_id(&self, profile_id: String, profile_picture: Option<String>) -> Result<(), RepositoryError>;
+    async fn update_banner_by_id(&self, profile_id: String, banner: Option<String>) -> Result<(), RepositoryError>;
+    // Fuzzy matches on username and display name of public profiles, best matches first
+    async fn search(&self, query: &str, after: Option<SearchCursor>, limit: i64) -> Result<Vec<(Profile, SearchCursor)>, RepositoryError>;
+    // Only public profiles
+    async fn list(&self, sort: ProfileSort, after: Option<ListCursor>, limit: i64) -> Result<Vec<(Profile, ListCursor)>, RepositoryError>;
     async fn get_total_profiles_count(&self) -> Result<i64, RepositoryError>;
 }
\ No newline at end of file
@@ -10,4 +10,7 @@ pub trait UserRepository: Send + Sync {
     async fn find_by_id(&self, id: &str) -> Result<User, RepositoryError>;
     async fn update(&self, user: &User) -> Result<(), RepositoryError>;
     async fn find_by_reset_password_token(&self, token: &str) -> Result<User, RepositoryError>;
+    async fn find_by_email_verification_token(&self, token: &str) -> Result<User, RepositoryError>;
+    async fn find_by_email_change_token(&self, token: &str) -> Result<User, RepositoryError>;
+    async fn find_by_mfa_challenge_id(&self, challenge_id: &str) -> Result<User, RepositoryError>;
 }
\ No newline at end of file
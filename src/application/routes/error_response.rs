@@ -6,12 +6,15 @@ use serde::Serialize;
 use tracing::log::error;
 
 use crate::application::connectors::auth_connector::AuthConnectorError;
+use crate::application::connectors::media_storage::MediaStorageError;
 use crate::application::errors::{RepositoryError, RouteError};
 use crate::application::errors::ApplicationError;
 use crate::application::services::profile_service::ProfileServiceError;
 use crate::application::services::user_service::UserProfileServiceError;
+use crate::domain::follow::FollowDomainError;
 use crate::domain::profile::ProfileDomainError;
 use crate::domain::user::UserDomainError;
+use crate::infrastructure::image_processing::ImageProcessingError;
 
 #[derive(Serialize)]
 pub struct ErrorResponse<'a> {
@@ -58,6 +61,22 @@ impl IntoHttpStatusCode for UserDomainError {
             UserDomainError::TooManyPasswordResetsRequested => 429,
             UserDomainError::InvalidPasswordResetToken => 400,
             UserDomainError::PasswordResetTokenExpired => 410,
+            UserDomainError::EmailNotVerified => 403,
+            UserDomainError::EmailAlreadyVerified => 409,
+            UserDomainError::TooManyEmailVerificationsRequested => 429,
+            UserDomainError::InvalidEmailVerificationToken => 400,
+            UserDomainError::EmailVerificationTokenExpired => 410,
+            UserDomainError::EmailUnchanged => 400,
+            UserDomainError::InvalidEmailChangeToken => 400,
+            UserDomainError::EmailChangeTokenExpired => 410,
+            UserDomainError::TotpAlreadyEnabled => 409,
+            UserDomainError::TotpNotEnrolled => 400,
+            UserDomainError::TotpNotEnabled => 400,
+            UserDomainError::InvalidTotpCode => 400,
+            UserDomainError::InvalidMfaChallenge => 400,
+            UserDomainError::MfaChallengeExpired => 410,
+            UserDomainError::InvalidRecoveryCode => 400,
+            UserDomainError::AccountLocked => 423,
             UserDomainError::ProfileDomainError(e) => e.status_code(),
         }
     }
@@ -66,7 +85,19 @@ impl IntoHttpStatusCode for UserDomainError {
 impl IntoHttpStatusCode for ProfileDomainError {
     fn status_code(&self) -> u16 {
         match self {
-            ProfileDomainError::InvalidUsername => 400
+            ProfileDomainError::InvalidUsername => 400,
+            ProfileDomainError::UsernameUnchanged => 400,
+            ProfileDomainError::UsernameChangeCooldown => 429,
+            ProfileDomainError::UsernameReserved => 400,
+            ProfileDomainError::InvalidVisibility => 400,
+        }
+    }
+}
+
+impl IntoHttpStatusCode for FollowDomainError {
+    fn status_code(&self) -> u16 {
+        match self {
+            FollowDomainError::CannotFollowSelf => 400
         }
     }
 }
@@ -76,6 +107,7 @@ impl IntoHttpStatusCode for UserProfileServiceError {
         match self {
             UserProfileServiceError::UnexpectedError(_) => unreachable!(),
             UserProfileServiceError::EmailAlreadyInUse => 409,
+            UserProfileServiceError::UsernameAlreadyInUse => 409,
             UserProfileServiceError::UserDomainError(e) => e.status_code(),
             UserProfileServiceError::ProfileDomainError(e) => e.status_code(),
             UserProfileServiceError::OutboxError(e) => e.status_code(),
@@ -91,7 +123,39 @@ impl IntoHttpStatusCode for ProfileServiceError {
     fn status_code(&self) -> u16 {
         match self {
             ProfileServiceError::UnexpectedError(_) => unreachable!(),
-            ProfileServiceError::RepositoryError(e) => e.status_code()
+            ProfileServiceError::UsernameAlreadyInUse => 409,
+            ProfileServiceError::InvalidSearchQuery => 400,
+            ProfileServiceError::InvalidCursor => 400,
+            ProfileServiceError::BatchTooLarge => 413,
+            ProfileServiceError::ProfileNotVisible => 403,
+            ProfileServiceError::AlreadyFollowing => 409,
+            ProfileServiceError::NotFollowing => 404,
+            ProfileServiceError::CannotBlockSelf => 400,
+            ProfileServiceError::AlreadyBlocked => 409,
+            ProfileServiceError::NotBlocked => 404,
+            ProfileServiceError::ProfileBlocked => 409,
+            ProfileServiceError::CannotMuteSelf => 400,
+            ProfileServiceError::AlreadyMuted => 409,
+            ProfileServiceError::NotMuted => 404,
+            ProfileServiceError::FollowDomainError(e) => e.status_code(),
+            ProfileServiceError::ProfileDomainError(e) => e.status_code(),
+            ProfileServiceError::TransactionError(e) => e.status_code(),
+            ProfileServiceError::RouterError(e) => e.status_code(),
+            ProfileServiceError::UserDomainError(e) => e.status_code(),
+            ProfileServiceError::RepositoryError(e) => e.status_code(),
+            ProfileServiceError::ImageProcessingError(e) => e.status_code(),
+            ProfileServiceError::MediaStorageError(e) => e.status_code(),
+        }
+    }
+}
+
+impl IntoHttpStatusCode for ImageProcessingError {
+    fn status_code(&self) -> u16 {
+        match self {
+            ImageProcessingError::UnexpectedError(_) => unreachable!(),
+            ImageProcessingError::UnsupportedImageType => 415,
+            ImageProcessingError::InvalidImage => 400,
+            ImageProcessingError::InvalidAspectRatio => 400,
         }
     }
 }
@@ -100,6 +164,15 @@ impl IntoHttpStatusCode for AuthConnectorError {
     fn status_code(&self) -> u16 {
         match self {
             AuthConnectorError::UnexpectedError(_) => unreachable!(),
+            AuthConnectorError::SessionNotFound => 404,
+        }
+    }
+}
+
+impl IntoHttpStatusCode for MediaStorageError {
+    fn status_code(&self) -> u16 {
+        match self {
+            MediaStorageError::UnexpectedError(_) => unreachable!(),
         }
     }
 }
@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::response::IntoResponse;
+use axum::Router;
+use axum::routing::get;
+use serde::Serialize;
+
+use crate::application::errors::ApplicationError;
+use crate::application::miscellaneous::ToJsonString;
+use crate::application::state::ServerState;
+
+// Only served on the internal listener, for other services (e.g. to hide content from blocked profiles)
+pub fn internal_router() -> Router<Arc<ServerState>> {
+    Router::new()
+        .route("/internal/profiles/:id/blocked/:other_id", get(get_is_blocked))
+}
+
+#[derive(Serialize, Debug)]
+pub struct IsBlockedResponse {
+    pub blocked: bool,
+}
+
+// Whether the first profile blocked the second one
+pub async fn get_is_blocked(State(server_state): State<Arc<ServerState>>, Path((profile_id, other_profile_id)): Path<(String, String)>) -> impl IntoResponse {
+    server_state.profile_service
+        .is_blocked(&profile_id, &other_profile_id).await
+        .map(|blocked| IsBlockedResponse { blocked })
+        .map_err(ApplicationError::from)
+        .and_then(|dto| dto.to_json_string())
+}
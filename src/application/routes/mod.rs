@@ -5,6 +5,7 @@ use axum::serve::IncomingStream;
 
 pub mod user_routes;
 pub mod profile_routes;
+pub mod internal_routes;
 mod error_response;
 
 #[derive(Clone)]
@@ -1,26 +1,48 @@
 use std::sync::Arc;
 
-use axum::{Extension, Router};
-use axum::extract::{Multipart, Path, State};
+use axum::{Extension, Json, Router};
+use axum::extract::{Multipart, Path, Query, State};
+use axum::http::header::LOCATION;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use axum::routing::{get, post};
-use serde::Serialize;
+use axum::routing::{delete, get, post};
+use serde::{Deserialize, Serialize};
 use tower_http::limit::RequestBodyLimitLayer;
 
-use crate::application::errors::ApplicationError;
+use crate::application::errors::{ApplicationError, RepositoryError};
 use crate::application::errors::RouteError;
 use crate::application::miscellaneous::ToJsonString;
+use crate::application::pagination::Page;
+use crate::application::repository_traits::read::profile_repository::{ProfileSort, ProfileVisibility};
+use crate::application::services::profile_service::ProfileServiceError;
 use crate::application::state::ServerState;
-use crate::infrastructure::session::SessionOption;
+use crate::domain::{Profile, Visibility};
+use crate::domain::profile::ProfileDomainError;
+use crate::infrastructure::session::{Session, SessionOption};
 
 pub fn profile_router() -> Router<Arc<ServerState>> {
     Router::new()
         .route("/profile/update", post(update_profile)
             // Set a different limit
             .layer(RequestBodyLimitLayer::new(5 * 1_000_000)))
+        .route("/profile/username", post(change_username))
+        .route("/profile/picture", post(update_profile_picture)
+            .layer(RequestBodyLimitLayer::new(5 * 1_000_000)))
+        .route("/profile/banner", post(update_banner)
+            .layer(RequestBodyLimitLayer::new(5 * 1_000_000)))
+        .route("/profile/blocks", get(get_blocked).post(block_profile))
+        .route("/profile/blocks/:id", delete(unblock_profile))
+        .route("/profile/mutes", get(get_muted).post(mute_profile))
+        .route("/profile/mutes/:id", delete(unmute_profile))
 
         .route("/profiles/:id", get(get_profile))
+        .route("/profiles/search", get(search_profiles))
+        .route("/profiles", get(list_profiles))
+        .route("/profiles/batch", post(get_profiles_batch))
+        .route("/profiles/:id/follow", post(follow_profile).delete(unfollow_profile))
+        .route("/profiles/:id/followers", get(get_followers))
+        .route("/profiles/:id/following", get(get_following))
+        .route("/profiles/by-username/:username", get(get_profile_by_username))
         .route("/profiles/count", get(get_total_profiles_count))
 }
 
@@ -32,23 +54,306 @@ pub struct GetProfileResponseDTO {
     pub bio: Option<String>,
     pub banner: Option<String>,
     pub profile_picture: Option<String>,
+    pub follower_count: i64,
+    pub following_count: i64,
+    // Only shown to the owner, anyone else would learn which fields are hidden from them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name_visibility: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bio_visibility: Option<&'static str>,
 }
 
-pub async fn get_profile(State(server_state): State<Arc<ServerState>>, Path(profile_id): Path<String>) -> impl IntoResponse {
-    server_state.profile_service
-        .find_profile_by_id(profile_id).await
-        .map(|profile| GetProfileResponseDTO {
+#[derive(Serialize, Debug)]
+pub struct ProfilePageResponseDTO {
+    pub profiles: Vec<GetProfileResponseDTO>,
+    pub next_cursor: Option<String>,
+}
+
+impl From<Page<Profile>> for ProfilePageResponseDTO {
+    fn from(page: Page<Profile>) -> Self {
+        ProfilePageResponseDTO {
+            profiles: page.items.into_iter().map(GetProfileResponseDTO::from).collect(),
+            next_cursor: page.next_cursor,
+        }
+    }
+}
+
+impl From<Profile> for GetProfileResponseDTO {
+    fn from(profile: Profile) -> Self {
+        GetProfileResponseDTO {
             id: profile.id,
             username: profile.username,
             display_name: profile.display_name,
             bio: profile.bio,
             banner: profile.banner,
             profile_picture: profile.profile_picture,
+            follower_count: profile.follower_count,
+            following_count: profile.following_count,
+            visibility: None,
+            display_name_visibility: None,
+            bio_visibility: None,
+        }
+    }
+}
+
+impl GetProfileResponseDTO {
+    fn for_viewer(profile: Profile, viewer: Option<&Session>) -> Self {
+        let owner = viewer.is_some_and(|viewer| viewer.profile_id == profile.id);
+        let setting = |visibility: Visibility| owner.then(|| visibility.as_str());
+
+        Self {
+            visibility: setting(profile.visibility),
+            display_name_visibility: setting(profile.display_name_visibility),
+            bio_visibility: setting(profile.bio_visibility),
+            ..Self::from(profile)
+        }
+    }
+}
+
+pub async fn get_profile(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, Path(profile_id): Path<String>) -> impl IntoResponse {
+    server_state.profile_service
+        .find_profile_by_id(profile_id, session.session.as_ref()).await
+        .map(|profile| GetProfileResponseDTO::for_viewer(profile, session.session.as_ref()))
+        .map_err(ApplicationError::from)
+        .and_then(|dto| dto.to_json_string())
+}
+
+// Usernames that were changed recently redirect to the current one
+pub async fn get_profile_by_username(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, Path(username): Path<String>) -> impl IntoResponse {
+    let viewer = session.session.as_ref();
+
+    let result = match server_state.profile_service.find_profile_by_username(&username, viewer).await {
+        Err(ProfileServiceError::RepositoryError(RepositoryError::ResourceNotFound)) => {
+            match server_state.profile_service.find_profile_by_previous_username(&username, viewer).await {
+                Ok(profile) => return (
+                    StatusCode::MOVED_PERMANENTLY,
+                    [(LOCATION, format!("/profiles/by-username/{}", profile.username))]
+                ).into_response(),
+                Err(e) => Err(e)
+            }
+        }
+        result => result
+    };
+
+    result
+        .map(|profile| GetProfileResponseDTO::for_viewer(profile, viewer))
+        .map_err(ApplicationError::from)
+        .and_then(|dto| dto.to_json_string())
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ChangeUsernameRequest {
+    pub username: String,
+}
+
+pub async fn change_username(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, Json(request): Json<ChangeUsernameRequest>) -> impl IntoResponse {
+    let session = match &session.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.profile_service
+        .change_username(&session.user_id, session.profile_id.clone(), request.username)
+        .await
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct SearchProfilesQuery {
+    pub q: String,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+pub async fn search_profiles(State(server_state): State<Arc<ServerState>>, Query(query): Query<SearchProfilesQuery>) -> impl IntoResponse {
+    server_state.profile_service
+        .search_profiles(&query.q, query.cursor.as_deref(), query.limit).await
+        .map(ProfilePageResponseDTO::from)
+        .map_err(ApplicationError::from)
+        .and_then(|dto| dto.to_json_string())
+}
+
+#[derive(Deserialize)]
+pub struct ListProfilesQuery {
+    pub sort: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+pub async fn list_profiles(State(server_state): State<Arc<ServerState>>, Query(query): Query<ListProfilesQuery>) -> impl IntoResponse {
+    let sort = match query.sort.as_deref() {
+        None | Some("newest") => ProfileSort::Newest,
+        Some("username") => ProfileSort::Username,
+        Some(_) => return StatusCode::BAD_REQUEST.into_response()
+    };
+
+    server_state.profile_service
+        .list_profiles(sort, query.cursor.as_deref(), query.limit).await
+        .map(ProfilePageResponseDTO::from)
+        .map_err(ApplicationError::from)
+        .and_then(|dto| dto.to_json_string())
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ProfileBatchRequest {
+    pub ids: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProfileBatchResponseDTO {
+    pub profiles: Vec<GetProfileResponseDTO>,
+    pub missing: Vec<String>,
+}
+
+pub async fn get_profiles_batch(State(server_state): State<Arc<ServerState>>, Json(request): Json<ProfileBatchRequest>) -> impl IntoResponse {
+    server_state.profile_service
+        .find_profiles_by_ids(request.ids).await
+        .map(|batch| ProfileBatchResponseDTO {
+            profiles: batch.profiles.into_iter().map(GetProfileResponseDTO::from).collect(),
+            missing: batch.missing,
         })
         .map_err(ApplicationError::from)
         .and_then(|dto| dto.to_json_string())
 }
 
+pub async fn follow_profile(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, Path(profile_id): Path<String>) -> impl IntoResponse {
+    let session = match &session.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.profile_service
+        .follow(session.profile_id.clone(), profile_id)
+        .await
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+pub async fn unfollow_profile(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, Path(profile_id): Path<String>) -> impl IntoResponse {
+    let session = match &session.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.profile_service
+        .unfollow(&session.profile_id, &profile_id)
+        .await
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct PageQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+pub async fn get_followers(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, Path(profile_id): Path<String>, Query(query): Query<PageQuery>) -> impl IntoResponse {
+    server_state.profile_service
+        .find_followers(&profile_id, session.session.as_ref(), query.cursor.as_deref(), query.limit).await
+        .map(ProfilePageResponseDTO::from)
+        .map_err(ApplicationError::from)
+        .and_then(|dto| dto.to_json_string())
+}
+
+pub async fn get_following(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, Path(profile_id): Path<String>, Query(query): Query<PageQuery>) -> impl IntoResponse {
+    server_state.profile_service
+        .find_following(&profile_id, session.session.as_ref(), query.cursor.as_deref(), query.limit).await
+        .map(ProfilePageResponseDTO::from)
+        .map_err(ApplicationError::from)
+        .and_then(|dto| dto.to_json_string())
+}
+
+#[derive(Deserialize)]
+pub struct TargetProfileRequest {
+    pub profile_id: String,
+}
+
+pub async fn block_profile(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, Json(request): Json<TargetProfileRequest>) -> impl IntoResponse {
+    let session = match &session.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.profile_service
+        .block(&session.profile_id, &request.profile_id)
+        .await
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+pub async fn unblock_profile(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, Path(profile_id): Path<String>) -> impl IntoResponse {
+    let session = match &session.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.profile_service
+        .unblock(&session.profile_id, &profile_id)
+        .await
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+pub async fn get_blocked(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, Query(query): Query<PageQuery>) -> impl IntoResponse {
+    let session = match &session.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.profile_service
+        .find_blocked(&session.profile_id, query.cursor.as_deref(), query.limit).await
+        .map(ProfilePageResponseDTO::from)
+        .map_err(ApplicationError::from)
+        .and_then(|dto| dto.to_json_string())
+        .into_response()
+}
+
+pub async fn mute_profile(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, Json(request): Json<TargetProfileRequest>) -> impl IntoResponse {
+    let session = match &session.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.profile_service
+        .mute(&session.profile_id, &request.profile_id)
+        .await
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+pub async fn unmute_profile(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, Path(profile_id): Path<String>) -> impl IntoResponse {
+    let session = match &session.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.profile_service
+        .unmute(&session.profile_id, &profile_id)
+        .await
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+pub async fn get_muted(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, Query(query): Query<PageQuery>) -> impl IntoResponse {
+    let session = match &session.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.profile_service
+        .find_muted(&session.profile_id, query.cursor.as_deref(), query.limit).await
+        .map(ProfilePageResponseDTO::from)
+        .map_err(ApplicationError::from)
+        .and_then(|dto| dto.to_json_string())
+        .into_response()
+}
+
 pub async fn get_total_profiles_count(State(server_state): State<Arc<ServerState>>) -> impl IntoResponse {
     server_state.profile_service
         .get_total_profiles_count()
@@ -67,33 +372,126 @@ pub async fn update_profile(State(server_state): State<Arc<ServerState>>, sessio
     // Parse multipart
     let multipart_result = parse_update_profile_multipart(multipart).await;
 
-    let (display_name, bio) = match multipart_result {
-        Some(tuple) => tuple,
+    let form = match multipart_result {
+        Some(form) => form,
         None => return ApplicationError::from(RouteError::InvalidMultipart).into_response()
     };
 
+    let visibility = match parse_visibility_settings(&form) {
+        Ok(visibility) => visibility,
+        Err(e) => return ApplicationError::from(ProfileServiceError::from(e)).into_response()
+    };
+
     // Update profile
     server_state.profile_service
-        .update_profile_by_id(session.profile_id.clone(), display_name, bio)
+        .update_profile_by_id(&session.user_id, session.profile_id.clone(), form.display_name, form.bio, visibility)
+        .await
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+#[derive(Serialize)]
+struct UpdateProfilePictureResponse {
+    pub profile_picture: String,
+}
+
+pub async fn update_profile_picture(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, multipart: Multipart) -> impl IntoResponse {
+    let session = match &session.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    let image = match parse_image_multipart(multipart, "picture").await {
+        Some(image) => image,
+        None => return ApplicationError::from(RouteError::InvalidMultipart).into_response()
+    };
+
+    server_state.profile_service
+        .update_profile_picture(&session.user_id, session.profile_id.clone(), image)
         .await
         .map_err(ApplicationError::from)
+        .and_then(|profile_picture| UpdateProfilePictureResponse {
+            profile_picture,
+        }.to_json_string())
         .into_response()
 }
 
-async fn parse_update_profile_multipart(mut multipart: Multipart) -> Option<(Option<String>, Option<String>)> {
-    let mut display_name: Option<String> = None;
-    let mut bio: Option<String> = None;
+#[derive(Serialize)]
+struct UpdateBannerResponse {
+    pub banner: String,
+}
+
+pub async fn update_banner(State(server_state): State<Arc<ServerState>>, session: Extension<SessionOption>, multipart: Multipart) -> impl IntoResponse {
+    let session = match &session.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    let image = match parse_image_multipart(multipart, "banner").await {
+        Some(image) => image,
+        None => return ApplicationError::from(RouteError::InvalidMultipart).into_response()
+    };
+
+    server_state.profile_service
+        .update_banner(&session.user_id, session.profile_id.clone(), image)
+        .await
+        .map_err(ApplicationError::from)
+        .and_then(|banner| UpdateBannerResponse {
+            banner,
+        }.to_json_string())
+        .into_response()
+}
+
+// Returns the bytes of the given field, other fields are ignored
+async fn parse_image_multipart(mut multipart: Multipart, field_name: &str) -> Option<Vec<u8>> {
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() == Some(field_name) {
+            return field.bytes().await.ok().map(|data| data.to_vec());
+        }
+    };
+
+    None
+}
+
+#[derive(Default)]
+struct UpdateProfileForm {
+    display_name: Option<String>,
+    bio: Option<String>,
+    visibility: Option<String>,
+    display_name_visibility: Option<String>,
+    bio_visibility: Option<String>,
+}
+
+async fn parse_update_profile_multipart(mut multipart: Multipart) -> Option<UpdateProfileForm> {
+    let mut form = UpdateProfileForm::default();
 
     while let Ok(Some(field)) = multipart.next_field().await {
         let name = field.name()?.to_string();
         let data = field.bytes().await.ok()?;
+        let value = Some(String::from_utf8(data.to_vec()).ok()?);
 
         match name.as_str() {
-            "display_name" => display_name = Some(String::from_utf8(data.to_vec()).ok()?),
-            "bio" => bio = Some(String::from_utf8(data.to_vec()).ok()?),
+            "display_name" => form.display_name = value,
+            "bio" => form.bio = value,
+            "visibility" => form.visibility = value,
+            "display_name_visibility" => form.display_name_visibility = value,
+            "bio_visibility" => form.bio_visibility = value,
             _ => {}
         };
     };
 
-    Some((display_name, bio))
+    Some(form)
+}
+
+// Settings that aren't in the form stay unchanged
+fn parse_visibility_settings(form: &UpdateProfileForm) -> Result<ProfileVisibility, ProfileDomainError> {
+    let parse = |value: &Option<String>| value.as_deref()
+        .map(|value| value.parse::<Visibility>())
+        .transpose();
+
+    Ok(ProfileVisibility {
+        profile: parse(&form.visibility)?,
+        display_name: parse(&form.display_name_visibility)?,
+        bio: parse(&form.bio_visibility)?,
+    })
 }
\ No newline at end of file
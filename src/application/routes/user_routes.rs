@@ -1,27 +1,55 @@
 use std::sync::Arc;
+use std::time::Duration;
 
-use axum::{Extension, Json, Router};
-use axum::extract::{ConnectInfo, State};
+use axum::{Extension, Json, middleware, Router};
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::http::header::USER_AGENT;
 use axum::response::IntoResponse;
-use axum::routing::post;
+use axum::routing::{delete, get, post};
 use cookie::{Cookie, SameSite};
 use derive_name::with_name;
 use serde::Deserialize;
 use serde::Serialize;
 use tower_cookies::Cookies;
 
+use crate::application::connectors::auth_connector::{SessionInfo, SessionMetadata};
 use crate::application::errors::ApplicationError;
 use crate::application::miscellaneous::ToJsonString;
 use crate::application::routes::ConnectionInfo;
+use crate::application::services::user_service::{Availability, SignInOutcome};
 use crate::application::state::ServerState;
+use crate::infrastructure::http::middleware::rate_limit_layer::{rate_limit, RateLimiter};
 use crate::infrastructure::session::SessionOption;
 
-pub fn user_router() -> Router<Arc<ServerState>> {
+pub fn user_router(rate_limiter: &RateLimiter) -> Router<Arc<ServerState>> {
+    let minute = Duration::from_secs(60);
+
     Router::new()
-        .route("/user/request-reset-password", post(request_reset_password))
+        .route("/user/request-reset-password", post(request_reset_password)
+            .layer(middleware::from_fn_with_state(rate_limiter.rule("request-reset-password", 5, minute), rate_limit)))
         .route("/user/reset-password", post(reset_password))
-        .route("/user/signup", post(sign_up))
-        .route("/user/signin", post(sign_in))
+        .route("/user/signup", post(sign_up)
+            .layer(middleware::from_fn_with_state(rate_limiter.rule("signup", 5, minute), rate_limit)))
+        .route("/user/signin", post(sign_in)
+            .layer(middleware::from_fn_with_state(rate_limiter.rule("signin", 10, minute), rate_limit)))
+        .route("/user/signin/mfa", post(sign_in_mfa))
+        // Checked as the user types, so it gets its own budget, still low enough to make enumerating emails slow
+        .route("/user/availability", get(check_availability)
+            .layer(middleware::from_fn_with_state(rate_limiter.rule("availability", 20, minute), rate_limit)))
+        .route("/user/signout", post(sign_out))
+        .route("/user/signout-everywhere", post(sign_out_everywhere))
+        .route("/user/verify-email", post(verify_email))
+        .route("/user/resend-verification", post(resend_verification))
+        .route("/user/change-password", post(change_password))
+        .route("/user/change-email", post(change_email))
+        .route("/user/confirm-email-change", post(confirm_email_change))
+        .route("/user/totp/enroll", post(enroll_totp))
+        .route("/user/totp/confirm", post(confirm_totp))
+        .route("/user/totp/disable", post(disable_totp))
+        .route("/user/totp/recovery-codes", post(regenerate_recovery_codes))
+        .route("/user/sessions", get(list_sessions))
+        .route("/user/sessions/:id", delete(revoke_session))
 }
 
 #[derive(Serialize)]
@@ -42,6 +70,18 @@ fn handle_sign_in_up(domain: String, cookies: &Cookies,
     }.to_json_string()
 }
 
+fn session_metadata(info: &ConnectionInfo, headers: &HeaderMap) -> SessionMetadata {
+    let device = headers.get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("unknown")
+        .to_string();
+
+    SessionMetadata {
+        ip_address: info.remote_addr.ip().to_string(),
+        device,
+    }
+}
+
 #[derive(Deserialize)]
 pub struct SignInForm {
     pub email: String,
@@ -50,12 +90,60 @@ pub struct SignInForm {
 
 pub async fn sign_in(Extension(_session_option): Extension<SessionOption>,
                      State(server_state): State<Arc<ServerState>>,
+                     ConnectInfo(info): ConnectInfo<ConnectionInfo>, headers: HeaderMap,
                      cookies: Cookies, Json(signin): Json<SignInForm>)
                      -> impl IntoResponse
 {
-    server_state.user_service.sign_in(&signin.email, &signin.password).await
+    let metadata = session_metadata(&info, &headers);
+
+    server_state.user_service.sign_in(&signin.email, &signin.password, metadata).await
+        .map_err(ApplicationError::from)
+        .and_then(|outcome| match outcome {
+            SignInOutcome::Session { profile_id, session_id } =>
+                handle_sign_in_up(server_state.domain.clone(), &cookies, profile_id, session_id),
+            SignInOutcome::MfaRequired { challenge_id } => MfaChallengeResponse {
+                mfa_challenge_id: challenge_id,
+            }.to_json_string()
+        })
+}
+
+#[derive(Serialize)]
+struct MfaChallengeResponse {
+    pub mfa_challenge_id: String,
+}
+
+// Either a TOTP code or one of the recovery codes completes the challenge
+#[derive(Deserialize)]
+pub struct SignInMfaForm {
+    pub challenge_id: String,
+    pub code: Option<String>,
+    pub recovery_code: Option<String>,
+}
+
+pub async fn sign_in_mfa(State(server_state): State<Arc<ServerState>>,
+                         ConnectInfo(info): ConnectInfo<ConnectionInfo>, headers: HeaderMap,
+                         cookies: Cookies, Json(form): Json<SignInMfaForm>)
+                         -> impl IntoResponse
+{
+    let metadata = session_metadata(&info, &headers);
+
+    let result = match (&form.code, &form.recovery_code) {
+        (Some(code), None) => server_state.user_service
+            .complete_mfa_sign_in(&form.challenge_id, code, metadata).await,
+        (None, Some(recovery_code)) => server_state.user_service
+            .complete_mfa_sign_in_with_recovery_code(&form.challenge_id, recovery_code, metadata).await,
+        _ => return StatusCode::BAD_REQUEST.into_response()
+    };
+
+    result
         .map_err(ApplicationError::from)
         .and_then(|(profile_id, session)| handle_sign_in_up(server_state.domain.clone(), &cookies, profile_id, session))
+        .into_response()
+}
+
+#[derive(Serialize)]
+struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
 }
 
 #[derive(Deserialize)]
@@ -66,13 +154,173 @@ pub struct SignUpForm {
 }
 
 pub async fn sign_up(State(server_state): State<Arc<ServerState>>,
+                     ConnectInfo(info): ConnectInfo<ConnectionInfo>, headers: HeaderMap,
                      cookies: Cookies, Json(signup): Json<SignUpForm>) -> impl IntoResponse
 {
-    server_state.user_service.sign_up(signup.email, signup.password, signup.username).await
+    let metadata = session_metadata(&info, &headers);
+
+    server_state.user_service.sign_up(signup.email, signup.password, signup.username, metadata).await
         .map_err(ApplicationError::from)
         .and_then(|(profile_id, session)| handle_sign_in_up(server_state.domain.clone(), &cookies, profile_id, session))
 }
 
+pub async fn sign_out(State(server_state): State<Arc<ServerState>>,
+                      cookies: Cookies)
+                      -> impl IntoResponse
+{
+    let session_id = match cookies.get("session_id") {
+        Some(cookie) => cookie.value().to_string(),
+        None => return StatusCode::OK.into_response()
+    };
+
+    let result = server_state.user_service.sign_out(session_id).await;
+
+    cookies.remove(create_session_cookie(server_state.domain.clone(), String::new()));
+
+    result
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+pub async fn sign_out_everywhere(State(server_state): State<Arc<ServerState>>,
+                                 Extension(session_option): Extension<SessionOption>,
+                                 cookies: Cookies)
+                                 -> impl IntoResponse
+{
+    let session = match &session_option.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    let result = server_state.user_service.sign_out_everywhere(session.user_id.clone()).await;
+
+    cookies.remove(create_session_cookie(server_state.domain.clone(), String::new()));
+
+    result
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+#[derive(Serialize)]
+struct SessionResponse {
+    pub id: String,
+    pub device: String,
+    pub ip_address: String,
+    pub created_at: i64,
+    pub last_seen_at: i64,
+    pub current: bool,
+}
+
+impl From<SessionInfo> for SessionResponse {
+    fn from(session: SessionInfo) -> Self {
+        SessionResponse {
+            id: session.id,
+            device: session.device,
+            ip_address: session.ip_address,
+            created_at: session.created_at.unix_timestamp(),
+            last_seen_at: session.last_seen_at.unix_timestamp(),
+            current: session.current,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SessionsResponse {
+    pub sessions: Vec<SessionResponse>,
+}
+
+pub async fn list_sessions(State(server_state): State<Arc<ServerState>>,
+                           Extension(session_option): Extension<SessionOption>,
+                           cookies: Cookies)
+                           -> impl IntoResponse
+{
+    let session = match &session_option.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    let current_session_token = cookies.get("session_id")
+        .map(|cookie| cookie.value().to_string());
+
+    server_state.user_service.list_sessions(session.user_id.clone(), current_session_token).await
+        .map_err(ApplicationError::from)
+        .and_then(|sessions| SessionsResponse {
+            sessions: sessions.into_iter().map(SessionResponse::from).collect(),
+        }.to_json_string())
+        .into_response()
+}
+
+pub async fn revoke_session(State(server_state): State<Arc<ServerState>>,
+                            Extension(session_option): Extension<SessionOption>,
+                            Path(session_id): Path<String>)
+                            -> impl IntoResponse
+{
+    let session = match &session_option.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.user_service.revoke_session(session.user_id.clone(), session_id).await
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct AvailabilityQuery {
+    pub username: Option<String>,
+    pub email: Option<String>,
+}
+
+// Only the fields that were asked for are included
+#[derive(Serialize)]
+struct AvailabilityResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<&'static str>,
+}
+
+fn availability_status(availability: Availability) -> &'static str {
+    match availability {
+        Availability::Available => "available",
+        Availability::Taken => "taken",
+        Availability::Invalid => "invalid",
+        Availability::Reserved => "reserved",
+    }
+}
+
+pub async fn check_availability(State(server_state): State<Arc<ServerState>>,
+                                Query(query): Query<AvailabilityQuery>)
+                                -> impl IntoResponse
+{
+    if query.username.is_none() && query.email.is_none() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let username = match &query.username {
+        Some(username) => match server_state.user_service.check_username_availability(username).await {
+            Ok(availability) => Some(availability_status(availability)),
+            Err(e) => return ApplicationError::from(e).into_response()
+        },
+        None => None
+    };
+
+    let email = match &query.email {
+        Some(email) => match server_state.user_service.check_email_availability(email).await {
+            Ok(availability) => Some(availability_status(availability)),
+            Err(e) => return ApplicationError::from(e).into_response()
+        },
+        None => None
+    };
+
+    AvailabilityResponse {
+        username,
+        email,
+    }
+        .to_json_string()
+        .into_response()
+}
+
 fn create_session_cookie(domain: String, session: String) -> Cookie<'static> {
     let mut cookie = Cookie::new("session_id", session);
     cookie.set_http_only(true);
@@ -113,3 +361,183 @@ pub async fn reset_password(State(server_state): State<Arc<ServerState>>,
         .await
         .map_err(ApplicationError::from)
 }
+
+#[derive(Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+pub async fn verify_email(State(server_state): State<Arc<ServerState>>,
+                          Json(request): Json<VerifyEmailRequest>)
+                          -> impl IntoResponse
+{
+    server_state.user_service.verify_email(&request.token)
+        .await
+        .map_err(ApplicationError::from)
+}
+
+pub async fn resend_verification(State(server_state): State<Arc<ServerState>>,
+                                 Extension(session_option): Extension<SessionOption>)
+                                 -> impl IntoResponse
+{
+    let session = match &session_option.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.user_service.resend_email_verification(&session.user_id)
+        .await
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+pub async fn change_password(State(server_state): State<Arc<ServerState>>,
+                             Extension(session_option): Extension<SessionOption>,
+                             Json(request): Json<ChangePasswordRequest>)
+                             -> impl IntoResponse
+{
+    let session = match &session_option.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.user_service
+        .change_password(&session.user_id, &request.current_password, &request.new_password)
+        .await
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ChangeEmailRequest {
+    pub password: String,
+    pub new_email: String,
+}
+
+pub async fn change_email(State(server_state): State<Arc<ServerState>>,
+                          Extension(session_option): Extension<SessionOption>,
+                          Json(request): Json<ChangeEmailRequest>)
+                          -> impl IntoResponse
+{
+    let session = match &session_option.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.user_service
+        .request_email_change(&session.user_id, &request.password, &request.new_email)
+        .await
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmEmailChangeRequest {
+    pub token: String,
+}
+
+pub async fn confirm_email_change(State(server_state): State<Arc<ServerState>>,
+                                  Json(request): Json<ConfirmEmailChangeRequest>)
+                                  -> impl IntoResponse
+{
+    server_state.user_service.confirm_email_change(&request.token)
+        .await
+        .map_err(ApplicationError::from)
+}
+
+#[derive(Serialize)]
+struct EnrollTotpResponse {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+pub async fn enroll_totp(State(server_state): State<Arc<ServerState>>,
+                         Extension(session_option): Extension<SessionOption>)
+                         -> impl IntoResponse
+{
+    let session = match &session_option.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.user_service.enroll_totp(&session.user_id)
+        .await
+        .map_err(ApplicationError::from)
+        .and_then(|enrollment| EnrollTotpResponse {
+            secret: enrollment.secret,
+            provisioning_uri: enrollment.provisioning_uri,
+        }.to_json_string())
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmTotpRequest {
+    pub code: String,
+}
+
+pub async fn confirm_totp(State(server_state): State<Arc<ServerState>>,
+                          Extension(session_option): Extension<SessionOption>,
+                          Json(request): Json<ConfirmTotpRequest>)
+                          -> impl IntoResponse
+{
+    let session = match &session_option.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.user_service.confirm_totp(&session.user_id, &request.code)
+        .await
+        .map_err(ApplicationError::from)
+        .and_then(|recovery_codes| RecoveryCodesResponse { recovery_codes }.to_json_string())
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct DisableTotpRequest {
+    pub password: String,
+    pub code: String,
+}
+
+pub async fn disable_totp(State(server_state): State<Arc<ServerState>>,
+                          Extension(session_option): Extension<SessionOption>,
+                          Json(request): Json<DisableTotpRequest>)
+                          -> impl IntoResponse
+{
+    let session = match &session_option.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.user_service.disable_totp(&session.user_id, &request.password, &request.code)
+        .await
+        .map_err(ApplicationError::from)
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct RegenerateRecoveryCodesRequest {
+    pub password: String,
+}
+
+pub async fn regenerate_recovery_codes(State(server_state): State<Arc<ServerState>>,
+                                       Extension(session_option): Extension<SessionOption>,
+                                       Json(request): Json<RegenerateRecoveryCodesRequest>)
+                                       -> impl IntoResponse
+{
+    let session = match &session_option.session {
+        Some(s) => s,
+        None => return StatusCode::UNAUTHORIZED.into_response()
+    };
+
+    server_state.user_service.regenerate_recovery_codes(&session.user_id, &request.password)
+        .await
+        .map_err(ApplicationError::from)
+        .and_then(|recovery_codes| RecoveryCodesResponse { recovery_codes }.to_json_string())
+        .into_response()
+}
@@ -1,48 +1,751 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
 use error_conversion_macro::ErrorEnum;
+use figure_lib::queue::integration::domain_event_dispatcher::DomainEventDispatcher;
+use figure_lib::queue::internal_event_router::RouterError;
+use figure_lib::rdbs::transaction::postgres_transaction::TransactionManager;
+use figure_lib::rdbs::transaction::TransactionError;
 use thiserror::Error;
+use time::OffsetDateTime;
+use tracing::log::error;
+use uuid::Uuid;
 
+use crate::application::connectors::media_storage::{MediaStorage, MediaStorageError};
+use crate::application::domain_event_dispatcher::{DomainEvent, DomainEventDiscriminants};
 use crate::application::errors::RepositoryError;
-use crate::application::repository_traits::read::profile_repository::ProfileRepository;
-use crate::domain::Profile;
+use crate::application::pagination;
+use crate::application::pagination::{DatedCursor, Page};
+use crate::application::repository_traits::read::block_repository::BlockRepository;
+use crate::application::repository_traits::read::follow_repository::FollowRepository;
+use crate::application::repository_traits::read::profile_repository::{ListCursor, ProfileRepository, ProfileSort, ProfileVisibility, SearchCursor};
+use crate::application::repository_traits::read::user_repository::UserRepository;
+use crate::application::state::DomainEventHandlerState;
+use crate::domain::{Follow, Profile};
+use crate::domain::follow::FollowDomainError;
+use crate::domain::profile::{Audience, ProfileDomainError, USERNAME_REDIRECT_GRACE_PERIOD};
+use crate::domain::user::UserDomainError;
+use crate::infrastructure::image_processing;
+use crate::infrastructure::session::Session;
+use crate::infrastructure::image_processing::{ImageProcessingError, ProcessedImage};
+
+// Largest first, the stored url points to the first size
+const PROFILE_PICTURE_SIZES: [u32; 3] = [400, 200, 64];
+
+// Banners are cropped to 3:1, anything narrower than 2:1 is rejected
+const BANNER_WIDTHS: [u32; 2] = [1500, 750];
+const BANNER_MIN_RATIO: f64 = 2.0;
+const BANNER_RATIO: f64 = 3.0;
+
+const MAX_SEARCH_QUERY_LENGTH: usize = 50;
+
+pub const MAX_BATCH_SIZE: usize = 100;
+
+// Batches and listings only contain public profiles, showing the fields anyone can see
+pub struct ProfileBatch {
+    // In the order they were requested
+    pub profiles: Vec<Profile>,
+    // Including profiles that aren't public
+    pub missing: Vec<String>,
+}
 
 pub struct ProfileService {
+    transaction_manager: TransactionManager,
+    domain_event_dispatcher: Arc<DomainEventDispatcher
+    <DomainEventDiscriminants, DomainEvent, Arc<DomainEventHandlerState>>>,
     profile_repository: Box<dyn ProfileRepository>,
+    user_repository: Box<dyn UserRepository>,
+    follow_repository: Box<dyn FollowRepository>,
+    block_repository: Box<dyn BlockRepository>,
+    verified_email_required_for_profile_update: bool,
+    media_storage: Box<dyn MediaStorage>,
 }
 
 #[derive(Debug, ErrorEnum, Error)]
 pub enum ProfileServiceError {
+    #[error("username-already-in-use")]
+    UsernameAlreadyInUse,
+
+    #[error("invalid-search-query")]
+    InvalidSearchQuery,
+
+    #[error("invalid-cursor")]
+    InvalidCursor,
+
+    #[error("batch-too-large")]
+    BatchTooLarge,
+
+    #[error("profile-not-visible")]
+    ProfileNotVisible,
+
+    #[error("already-following")]
+    AlreadyFollowing,
+
+    #[error("not-following")]
+    NotFollowing,
+
+    #[error("cannot-block-self")]
+    CannotBlockSelf,
+
+    #[error("already-blocked")]
+    AlreadyBlocked,
+
+    #[error("not-blocked")]
+    NotBlocked,
+
+    #[error("profile-blocked")]
+    ProfileBlocked,
+
+    #[error("cannot-mute-self")]
+    CannotMuteSelf,
+
+    #[error("already-muted")]
+    AlreadyMuted,
+
+    #[error("not-muted")]
+    NotMuted,
+
+    #[without_anyhow]
+    #[error(transparent)]
+    FollowDomainError(FollowDomainError),
+
     #[error(transparent)]
     UnexpectedError(anyhow::Error),
 
+    #[without_anyhow]
+    #[error(transparent)]
+    ProfileDomainError(ProfileDomainError),
+
+    #[error(transparent)]
+    TransactionError(TransactionError),
+
+    #[error(transparent)]
+    RouterError(RouterError),
+
+    #[error(transparent)]
+    UserDomainError(UserDomainError),
+
     #[error(transparent)]
     RepositoryError(RepositoryError),
+
+    #[error(transparent)]
+    ImageProcessingError(ImageProcessingError),
+
+    #[error(transparent)]
+    MediaStorageError(MediaStorageError),
 }
 
 impl ProfileService {
-    pub fn new(profile_repository: Box<dyn ProfileRepository>) -> Self {
+    pub fn new(transaction_manager: TransactionManager,
+               domain_event_dispatcher: Arc<DomainEventDispatcher<DomainEventDiscriminants, DomainEvent, Arc<DomainEventHandlerState>>>,
+               profile_repository: Box<dyn ProfileRepository>,
+               user_repository: Box<dyn UserRepository>,
+               follow_repository: Box<dyn FollowRepository>,
+               block_repository: Box<dyn BlockRepository>,
+               verified_email_required_for_profile_update: bool,
+               media_storage: Box<dyn MediaStorage>) -> Self {
         Self {
+            transaction_manager,
+            domain_event_dispatcher,
             profile_repository,
+            user_repository,
+            follow_repository,
+            block_repository,
+            verified_email_required_for_profile_update,
+            media_storage,
         }
     }
 }
 
 impl ProfileService {
-    pub async fn find_profile_by_id(&self, profile_id: String) -> Result<Profile, ProfileServiceError> {
-        self
-            .profile_repository.find_by_id(profile_id)
+    pub async fn find_profile_by_id(&self, profile_id: String, viewer: Option<&Session>) -> Result<Profile, ProfileServiceError> {
+        let profile = self.profile_repository.find_by_id(profile_id).await?;
+
+        self.ensure_visible_to(profile, viewer).await
+    }
+
+    pub async fn find_profiles_by_ids(&self, profile_ids: Vec<String>) -> Result<ProfileBatch, ProfileServiceError> {
+        let mut seen = HashSet::new();
+        let unique_ids: Vec<String> = profile_ids.into_iter()
+            .filter(|id| seen.insert(id.clone()))
+            .collect();
+
+        if unique_ids.len() > MAX_BATCH_SIZE {
+            return Err(ProfileServiceError::BatchTooLarge);
+        }
+
+        if unique_ids.is_empty() {
+            return Ok(ProfileBatch {
+                profiles: Vec::new(),
+                missing: Vec::new(),
+            });
+        }
+
+        let mut found: HashMap<String, Profile> = self.profile_repository.find_by_ids(&unique_ids)
+            .await?
+            .into_iter()
+            .map(|profile| (profile.get_id(), profile))
+            .collect();
+
+        let mut profiles = Vec::new();
+        let mut missing = Vec::new();
+
+        for id in unique_ids {
+            match found.remove(&id) {
+                Some(profile) if profile.is_visible_to(Audience::Anonymous) =>
+                    profiles.push(profile.with_fields_visible_to(Audience::Anonymous)),
+                _ => missing.push(id)
+            }
+        }
+
+        Ok(ProfileBatch {
+            profiles,
+            missing,
+        })
+    }
+
+    pub async fn find_profile_by_username(&self, username: &str, viewer: Option<&Session>) -> Result<Profile, ProfileServiceError> {
+        let profile = self.profile_repository.find_by_username(username).await?;
+
+        self.ensure_visible_to(profile, viewer).await
+    }
+
+    // Falls back to usernames that were changed recently, so old links can be redirected
+    pub async fn find_profile_by_previous_username(&self, username: &str, viewer: Option<&Session>) -> Result<Profile, ProfileServiceError> {
+        let changed_after = OffsetDateTime::now_utc() - USERNAME_REDIRECT_GRACE_PERIOD;
+
+        let profile = self.profile_repository.find_by_previous_username(username, changed_after).await?;
+
+        self.ensure_visible_to(profile, viewer).await
+    }
+
+    // Profiles that blocked the viewer look like they don't exist,
+    // fields the viewer isn't allowed to see are left out
+    async fn ensure_visible_to(&self, profile: Profile, viewer: Option<&Session>) -> Result<Profile, ProfileServiceError> {
+        let audience = match viewer {
+            Some(viewer) => {
+                if self.block_repository.is_blocked(&profile.id, &viewer.profile_id).await? {
+                    return Err(RepositoryError::ResourceNotFound.into());
+                }
+
+                self.audience_of(&profile, viewer).await?
+            }
+            None => Audience::Anonymous
+        };
+
+        if !profile.is_visible_to(audience) {
+            return Err(ProfileServiceError::ProfileNotVisible);
+        }
+
+        Ok(profile.with_fields_visible_to(audience))
+    }
+
+    async fn audience_of(&self, profile: &Profile, viewer: &Session) -> Result<Audience, ProfileServiceError> {
+        if viewer.profile_id == profile.id {
+            return Ok(Audience::Owner);
+        }
+
+        // Only look up the follow when it makes a difference
+        if !profile.has_followers_only_content() {
+            return Ok(Audience::SignedIn);
+        }
+
+        match self.follow_repository.find(&viewer.profile_id, &profile.id).await {
+            Ok(_) => Ok(Audience::Follower),
+            Err(RepositoryError::ResourceNotFound) => Ok(Audience::SignedIn),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    pub async fn change_username(&self, user_id: &str, profile_id: String, new_username: String) -> Result<(), ProfileServiceError> {
+        self.ensure_profile_can_be_updated(user_id).await?;
+
+        let event = self.transaction_manager.transaction(|| async {
+            let mut profile = self.profile_repository.find_by_id(profile_id.clone()).await?;
+
+            let skeleton = Profile::validate_username(&new_username)?;
+            self.ensure_username_available(&skeleton, &profile.id).await?;
+
+            let old_username = profile.username.clone();
+            let event = profile.change_username(new_username.clone())?;
+
+            // The unique skeleton index catches a concurrent claim of the same username
+            self.profile_repository.change_username(&profile, &old_username)
+                .await
+                .map_err(|e| match e {
+                    RepositoryError::ConstraintConflict => ProfileServiceError::UsernameAlreadyInUse,
+                    e => e.into()
+                })?;
+
+            Ok::<_, ProfileServiceError>(event)
+        }).await??;
+
+        // Handlers shouldn't see a rename that could still roll back
+        self.domain_event_dispatcher.dispatch(event).await?;
+
+        Ok(())
+    }
+
+    // Taken by another profile, either currently or as a username that is still in its grace period
+    async fn ensure_username_available(&self, skeleton: &str, profile_id: &str) -> Result<(), ProfileServiceError> {
+        let changed_after = OffsetDateTime::now_utc() - USERNAME_REDIRECT_GRACE_PERIOD;
+
+        let current = self.profile_repository.find_by_username_skeleton(skeleton).await;
+        let previous = self.profile_repository.find_by_previous_username_skeleton(skeleton, changed_after).await;
+
+        for result in [current, previous] {
+            match result {
+                Ok(profile) if profile.id != profile_id => return Err(ProfileServiceError::UsernameAlreadyInUse),
+                Ok(_) | Err(RepositoryError::ResourceNotFound) => {}
+                Err(e) => return Err(e.into())
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn update_profile_by_id(&self, user_id: &str, profile_id: String, display_name: Option<String>, bio: Option<String>, visibility: ProfileVisibility) -> Result<(), ProfileServiceError> {
+        self.ensure_profile_can_be_updated(user_id).await?;
+
+        self.profile_repository.update_profile_by_id(profile_id, display_name, bio, visibility)
             .await
             .map_err(|e| e.into())
     }
 
-    pub async fn update_profile_by_id(&self, profile_id: String, display_name: Option<String>, bio: Option<String>) -> Result<(), ProfileServiceError> {
-        self.profile_repository.update_profile_by_id(profile_id, display_name, bio)
+    pub async fn update_profile_picture(&self, user_id: &str, profile_id: String, image: Vec<u8>) -> Result<String, ProfileServiceError> {
+        self.ensure_profile_can_be_updated(user_id).await?;
+
+        // Decoding and resizing is CPU bound, keep it off the async workers
+        let variants = tokio::task::spawn_blocking(move || {
+            let image = image_processing::decode(&image)?;
+            image_processing::square_variants(&image, &PROFILE_PICTURE_SIZES)
+        }).await
+            .map_err(|e| ProfileServiceError::UnexpectedError(e.into()))??;
+
+        let previous = self.profile_repository.find_by_id(profile_id.clone()).await?.profile_picture;
+
+        let key = format!("profile-pictures/{}/{}", profile_id, Uuid::new_v4());
+        let url = self.store_variants(&key, variants).await?;
+
+        self.profile_repository.update_profile_picture_by_id(profile_id, Some(url.clone())).await?;
+
+        if let Some(previous) = previous {
+            self.delete_variants(&previous).await;
+        }
+
+        Ok(url)
+    }
+
+    pub async fn update_banner(&self, user_id: &str, profile_id: String, image: Vec<u8>) -> Result<String, ProfileServiceError> {
+        self.ensure_profile_can_be_updated(user_id).await?;
+
+        let variants = tokio::task::spawn_blocking(move || {
+            let image = image_processing::decode(&image)?;
+            image_processing::wide_variants(&image, &BANNER_WIDTHS, BANNER_MIN_RATIO, BANNER_RATIO)
+        }).await
+            .map_err(|e| ProfileServiceError::UnexpectedError(e.into()))??;
+
+        let previous = self.profile_repository.find_by_id(profile_id.clone()).await?.banner;
+
+        let key = format!("banners/{}/{}", profile_id, Uuid::new_v4());
+        let url = self.store_variants(&key, variants).await?;
+
+        self.profile_repository.update_banner_by_id(profile_id, Some(url.clone())).await?;
+
+        if let Some(previous) = previous {
+            self.delete_variants(&previous).await;
+        }
+
+        Ok(url)
+    }
+
+    // Every variant is stored as {key}-{width}.webp, the url of the first one is returned
+    async fn store_variants(&self, key: &str, variants: Vec<ProcessedImage>) -> Result<String, ProfileServiceError> {
+        let mut urls = Vec::new();
+
+        for variant in variants {
+            let name = format!("{}-{}.{}", key, variant.width, image_processing::OUTPUT_EXTENSION);
+
+            urls.push(self.media_storage.put(&name, image_processing::OUTPUT_CONTENT_TYPE, variant.data).await?);
+        }
+
+        urls.into_iter()
+            .next()
+            .ok_or_else(|| ProfileServiceError::UnexpectedError(anyhow::Error::msg("No image variants were produced")))
+    }
+
+    // Removes every variant stored next to the given url. The new image is already saved at this point,
+    // so failures are only logged and leave an orphaned object behind
+    async fn delete_variants(&self, url: &str) {
+        let prefix = match self.media_storage.key_from_url(url)
+            .and_then(|key| key.rsplit_once('-').map(|(prefix, _)| format!("{prefix}-")))
+        {
+            Some(prefix) => prefix,
+            None => return
+        };
+
+        if let Err(e) = self.media_storage.delete_by_prefix(&prefix).await {
+            error!("Failed to delete replaced media {}: {}", prefix, e);
+        }
+    }
+
+    async fn ensure_profile_can_be_updated(&self, user_id: &str) -> Result<(), ProfileServiceError> {
+        if self.verified_email_required_for_profile_update {
+            self.user_repository.find_by_id(user_id)
+                .await?
+                .ensure_email_verified()?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn search_profiles(&self, query: &str, cursor: Option<&str>, limit: Option<i64>) -> Result<Page<Profile>, ProfileServiceError> {
+        let query = query.trim();
+
+        if query.is_empty() || query.chars().count() > MAX_SEARCH_QUERY_LENGTH {
+            return Err(ProfileServiceError::InvalidSearchQuery);
+        }
+
+        let after = match cursor {
+            Some(cursor) => Some(decode_search_cursor(cursor).ok_or(ProfileServiceError::InvalidCursor)?),
+            None => None
+        };
+
+        let limit = pagination::page_size(limit);
+
+        // One extra row tells whether there is a next page
+        let mut results = self.profile_repository.search(query, after, limit + 1).await?;
+
+        let next_cursor = match results.len() as i64 > limit {
+            true => {
+                results.truncate(limit as usize);
+                results.last().map(|(_, cursor)| encode_search_cursor(cursor))
+            }
+            false => None
+        };
+
+        Ok(Page {
+            items: results.into_iter().map(|(profile, _)| profile.with_fields_visible_to(Audience::Anonymous)).collect(),
+            next_cursor,
+        })
+    }
+
+    pub async fn list_profiles(&self, sort: ProfileSort, cursor: Option<&str>, limit: Option<i64>) -> Result<Page<Profile>, ProfileServiceError> {
+        let after = match cursor {
+            Some(cursor) => Some(decode_list_cursor(sort, cursor).ok_or(ProfileServiceError::InvalidCursor)?),
+            None => None
+        };
+
+        let limit = pagination::page_size(limit);
+
+        let mut results = self.profile_repository.list(sort, after, limit + 1).await?;
+
+        let next_cursor = match results.len() as i64 > limit {
+            true => {
+                results.truncate(limit as usize);
+                results.last().map(|(_, cursor)| encode_list_cursor(cursor))
+            }
+            false => None
+        };
+
+        Ok(Page {
+            items: results.into_iter().map(|(profile, _)| profile.with_fields_visible_to(Audience::Anonymous)).collect(),
+            next_cursor,
+        })
+    }
+
+    pub async fn follow(&self, follower_id: String, followee_id: String) -> Result<(), ProfileServiceError> {
+        let (follow, event) = Follow::follow(follower_id, followee_id)?;
+
+        self.transaction_manager.transaction(|| async {
+            self.profile_repository.find_by_id(follow.followee_id().to_string()).await?;
+
+            // Hidden from the follower when they are blocked, unblocking comes first when they did the blocking
+            if self.block_repository.is_blocked(follow.followee_id(), follow.follower_id()).await? {
+                return Err(RepositoryError::ResourceNotFound.into());
+            }
+
+            if self.block_repository.is_blocked(follow.follower_id(), follow.followee_id()).await? {
+                return Err(ProfileServiceError::ProfileBlocked);
+            }
+
+            self.follow_repository.insert(&follow)
+                .await
+                .map_err(|e| match e {
+                    RepositoryError::ConstraintConflict => ProfileServiceError::AlreadyFollowing,
+                    e => e.into()
+                })?;
+
+            Ok::<_, ProfileServiceError>(())
+        }).await??;
+
+        self.domain_event_dispatcher.dispatch(event).await?;
+
+        Ok(())
+    }
+
+    pub async fn unfollow(&self, follower_id: &str, followee_id: &str) -> Result<(), ProfileServiceError> {
+        let event = self.transaction_manager.transaction(|| async {
+            let follow = self.follow_repository.find(follower_id, followee_id)
+                .await
+                .map_err(|e| match e {
+                    RepositoryError::ResourceNotFound => ProfileServiceError::NotFollowing,
+                    e => e.into()
+                })?;
+
+            self.follow_repository.delete(&follow).await?;
+
+            Ok::<_, ProfileServiceError>(follow.unfollow())
+        }).await??;
+
+        self.domain_event_dispatcher.dispatch(event).await?;
+
+        Ok(())
+    }
+
+    // Also removes any follow between the two profiles, in both directions
+    pub async fn block(&self, blocker_id: &str, blocked_id: &str) -> Result<(), ProfileServiceError> {
+        if blocker_id == blocked_id {
+            return Err(ProfileServiceError::CannotBlockSelf);
+        }
+
+        let events = self.transaction_manager.transaction(|| async {
+            self.profile_repository.find_by_id(blocked_id.to_string()).await?;
+
+            self.block_repository.insert_block(blocker_id, blocked_id)
+                .await
+                .map_err(|e| match e {
+                    RepositoryError::ConstraintConflict => ProfileServiceError::AlreadyBlocked,
+                    e => e.into()
+                })?;
+
+            let mut events = Vec::new();
+
+            for (follower_id, followee_id) in [(blocker_id, blocked_id), (blocked_id, blocker_id)] {
+                let follow = match self.follow_repository.find(follower_id, followee_id).await {
+                    Ok(follow) => follow,
+                    Err(RepositoryError::ResourceNotFound) => continue,
+                    Err(e) => return Err(e.into())
+                };
+
+                self.follow_repository.delete(&follow).await?;
+
+                events.push(follow.unfollow());
+            }
+
+            Ok::<_, ProfileServiceError>(events)
+        }).await??;
+
+        for event in events {
+            self.domain_event_dispatcher.dispatch(event).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn unblock(&self, blocker_id: &str, blocked_id: &str) -> Result<(), ProfileServiceError> {
+        self.block_repository.delete_block(blocker_id, blocked_id)
+            .await
+            .map_err(|e| match e {
+                RepositoryError::ResourceNotFound => ProfileServiceError::NotBlocked,
+                e => e.into()
+            })
+    }
+
+    pub async fn is_blocked(&self, blocker_id: &str, blocked_id: &str) -> Result<bool, ProfileServiceError> {
+        self.block_repository.is_blocked(blocker_id, blocked_id)
             .await
             .map_err(|e| e.into())
     }
 
+    pub async fn find_blocked(&self, profile_id: &str, cursor: Option<&str>, limit: Option<i64>) -> Result<Page<Profile>, ProfileServiceError> {
+        let after = decode_dated_cursor(cursor)?;
+        let limit = pagination::page_size(limit);
+
+        let results = self.block_repository.find_blocked(profile_id, after, limit + 1).await?;
+
+        Ok(dated_page(results, limit))
+    }
+
+    pub async fn mute(&self, muter_id: &str, muted_id: &str) -> Result<(), ProfileServiceError> {
+        if muter_id == muted_id {
+            return Err(ProfileServiceError::CannotMuteSelf);
+        }
+
+        self.profile_repository.find_by_id(muted_id.to_string()).await?;
+
+        self.block_repository.insert_mute(muter_id, muted_id)
+            .await
+            .map_err(|e| match e {
+                RepositoryError::ConstraintConflict => ProfileServiceError::AlreadyMuted,
+                e => e.into()
+            })
+    }
+
+    pub async fn unmute(&self, muter_id: &str, muted_id: &str) -> Result<(), ProfileServiceError> {
+        self.block_repository.delete_mute(muter_id, muted_id)
+            .await
+            .map_err(|e| match e {
+                RepositoryError::ResourceNotFound => ProfileServiceError::NotMuted,
+                e => e.into()
+            })
+    }
+
+    pub async fn find_muted(&self, profile_id: &str, cursor: Option<&str>, limit: Option<i64>) -> Result<Page<Profile>, ProfileServiceError> {
+        let after = decode_dated_cursor(cursor)?;
+        let limit = pagination::page_size(limit);
+
+        let results = self.block_repository.find_muted(profile_id, after, limit + 1).await?;
+
+        Ok(dated_page(results, limit))
+    }
+
+    // Only when the viewer can see the profile itself
+    pub async fn find_followers(&self, profile_id: &str, viewer: Option<&Session>, cursor: Option<&str>, limit: Option<i64>) -> Result<Page<Profile>, ProfileServiceError> {
+        self.find_profile_by_id(profile_id.to_string(), viewer).await?;
+
+        let after = decode_dated_cursor(cursor)?;
+        let limit = pagination::page_size(limit);
+
+        let results = self.follow_repository.find_followers(profile_id, after, limit + 1).await?;
+        let (profiles, next_cursor) = truncate_dated_page(results, limit);
+
+        Ok(Page {
+            items: self.visible_profiles(profiles, viewer).await?,
+            next_cursor,
+        })
+    }
+
+    // Only when the viewer can see the profile itself
+    pub async fn find_following(&self, profile_id: &str, viewer: Option<&Session>, cursor: Option<&str>, limit: Option<i64>) -> Result<Page<Profile>, ProfileServiceError> {
+        self.find_profile_by_id(profile_id.to_string(), viewer).await?;
+
+        let after = decode_dated_cursor(cursor)?;
+        let limit = pagination::page_size(limit);
+
+        let results = self.follow_repository.find_following(profile_id, after, limit + 1).await?;
+        let (profiles, next_cursor) = truncate_dated_page(results, limit);
+
+        Ok(Page {
+            items: self.visible_profiles(profiles, viewer).await?,
+            next_cursor,
+        })
+    }
+
+    // Leaves out the profiles the viewer can't see, pages can end up shorter than the limit
+    async fn visible_profiles(&self, profiles: Vec<Profile>, viewer: Option<&Session>) -> Result<Vec<Profile>, ProfileServiceError> {
+        let viewer = match viewer {
+            Some(viewer) => viewer,
+            None => return Ok(profiles.into_iter()
+                .filter(|profile| profile.is_visible_to(Audience::Anonymous))
+                .map(|profile| profile.with_fields_visible_to(Audience::Anonymous))
+                .collect())
+        };
+
+        let profile_ids: Vec<String> = profiles.iter()
+            .map(|profile| profile.get_id())
+            .collect();
+
+        let blocked_by: HashSet<String> = self.block_repository.find_blocker_ids(&viewer.profile_id, &profile_ids)
+            .await?
+            .into_iter()
+            .collect();
+
+        let followed: HashSet<String> = self.follow_repository.find_followed_ids(&viewer.profile_id, &profile_ids)
+            .await?
+            .into_iter()
+            .collect();
+
+        Ok(profiles.into_iter()
+            .filter(|profile| !blocked_by.contains(&profile.id))
+            .filter_map(|profile| {
+                let audience = match (profile.id == viewer.profile_id, followed.contains(&profile.id)) {
+                    (true, _) => Audience::Owner,
+                    (false, true) => Audience::Follower,
+                    (false, false) => Audience::SignedIn,
+                };
+
+                match profile.is_visible_to(audience) {
+                    true => Some(profile.with_fields_visible_to(audience)),
+                    false => None
+                }
+            })
+            .collect())
+    }
+
     pub async fn get_total_profiles_count(&self) -> Result<i64, ProfileServiceError> {
         self.profile_repository.get_total_profiles_count()
             .await
             .map_err(|e| e.into())
     }
-}
\ No newline at end of file
+}
+
+fn encode_search_cursor(cursor: &SearchCursor) -> String {
+    pagination::encode_cursor(&[&cursor.rank.to_string(), &cursor.id])
+}
+
+fn decode_search_cursor(cursor: &str) -> Option<SearchCursor> {
+    match pagination::decode_cursor(cursor)?.as_slice() {
+        [rank, id] => Some(SearchCursor {
+            rank: rank.parse().ok()?,
+            id: id.clone(),
+        }),
+        _ => None
+    }
+}
+
+fn encode_list_cursor(cursor: &ListCursor) -> String {
+    match cursor {
+        ListCursor::Newest { created_at, id } =>
+            pagination::encode_cursor(&["newest", &created_at.unix_timestamp_nanos().to_string(), id]),
+        ListCursor::Username { username } =>
+            pagination::encode_cursor(&["username", username]),
+    }
+}
+
+// A cursor is only valid for the sort it was created with
+fn decode_list_cursor(sort: ProfileSort, cursor: &str) -> Option<ListCursor> {
+    match (sort, pagination::decode_cursor(cursor)?.as_slice()) {
+        (ProfileSort::Newest, [kind, created_at, id]) if kind == "newest" => Some(ListCursor::Newest {
+            created_at: OffsetDateTime::from_unix_timestamp_nanos(created_at.parse().ok()?).ok()?,
+            id: id.clone(),
+        }),
+        (ProfileSort::Username, [kind, username]) if kind == "username" => Some(ListCursor::Username {
+            username: username.clone(),
+        }),
+        _ => None
+    }
+}
+
+fn dated_page(results: Vec<(Profile, DatedCursor)>, limit: i64) -> Page<Profile> {
+    let (profiles, next_cursor) = truncate_dated_page(results, limit);
+
+    Page {
+        items: profiles.into_iter().map(|profile| profile.with_fields_visible_to(Audience::Anonymous)).collect(),
+        next_cursor,
+    }
+}
+
+// Results are fetched with one extra row to tell whether there is a next page
+fn truncate_dated_page(mut results: Vec<(Profile, DatedCursor)>, limit: i64) -> (Vec<Profile>, Option<String>) {
+    let next_cursor = match results.len() as i64 > limit {
+        true => {
+            results.truncate(limit as usize);
+            results.last().map(|(_, cursor)| cursor.encode())
+        }
+        false => None
+    };
+
+    (results.into_iter().map(|(profile, _)| profile).collect(), next_cursor)
+}
+
+fn decode_dated_cursor(cursor: Option<&str>) -> Result<Option<DatedCursor>, ProfileServiceError> {
+    let cursor = match cursor {
+        Some(cursor) => cursor,
+        None => return Ok(None)
+    };
+
+    DatedCursor::decode(cursor)
+        .map(Some)
+        .ok_or(ProfileServiceError::InvalidCursor)
+}
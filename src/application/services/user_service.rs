@@ -7,17 +7,21 @@ use figure_lib::rdbs::outbox_repository::{Outbox, OutboxError};
 use figure_lib::rdbs::transaction::postgres_transaction::TransactionManager;
 use figure_lib::rdbs::transaction::TransactionError;
 use thiserror::Error;
+use time::OffsetDateTime;
 use tracing::log::error;
 
-use crate::application::connectors::auth_connector::{AuthConnector, AuthConnectorError};
+use crate::application::connectors::auth_connector::{AuthConnector, AuthConnectorError, SessionInfo, SessionMetadata};
 use crate::application::domain_event_dispatcher::{DomainEvent, DomainEventDiscriminants};
 use crate::application::errors::RepositoryError;
 use crate::application::repository_traits::read::profile_repository::ProfileRepository;
 use crate::application::repository_traits::read::user_repository::UserRepository;
 use crate::application::state::DomainEventHandlerState;
 use crate::domain::{Profile, User};
-use crate::domain::profile::ProfileDomainError;
+use crate::domain::profile::{ProfileDomainError, USERNAME_REDIRECT_GRACE_PERIOD};
+use crate::domain::user::user::LockoutPolicy;
 use crate::domain::user::UserDomainError;
+use crate::infrastructure::secret_cipher::SecretCipher;
+use crate::infrastructure::totp;
 
 pub struct UserProfileService {
     transaction_manager: TransactionManager,
@@ -27,6 +31,27 @@ pub struct UserProfileService {
     profile_repository: Box<dyn ProfileRepository>,
     outbox_repository: Box<dyn Outbox>,
     auth_connector: Box<dyn AuthConnector>,
+    verified_email_required_for_sign_in: bool,
+    secret_cipher: SecretCipher,
+    lockout_policy: LockoutPolicy,
+}
+
+pub enum SignInOutcome {
+    Session { profile_id: String, session_id: String },
+    // The password was correct but a second factor is required to finish signing in
+    MfaRequired { challenge_id: String },
+}
+
+pub enum Availability {
+    Available,
+    Taken,
+    Invalid,
+    Reserved,
+}
+
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub provisioning_uri: String,
 }
 
 #[derive(Debug, ErrorEnum, Error)]
@@ -34,6 +59,9 @@ pub enum UserProfileServiceError {
     #[error("email-already-in-use")]
     EmailAlreadyInUse,
 
+    #[error("username-already-in-use")]
+    UsernameAlreadyInUse,
+
     #[error(transparent)]
     UserDomainError(UserDomainError),
 
@@ -62,7 +90,10 @@ impl UserProfileService {
                user_repository: Box<dyn UserRepository>,
                profile_repository: Box<dyn ProfileRepository>,
                outbox_repository: Box<dyn Outbox>,
-               auth_connector: Box<dyn AuthConnector>) -> Self {
+               auth_connector: Box<dyn AuthConnector>,
+               verified_email_required_for_sign_in: bool,
+               secret_cipher: SecretCipher,
+               lockout_policy: LockoutPolicy) -> Self {
         UserProfileService {
             user_repository,
             profile_repository,
@@ -70,68 +101,195 @@ impl UserProfileService {
             auth_connector,
             outbox_repository,
             domain_event_dispatcher,
+            verified_email_required_for_sign_in,
+            secret_cipher,
+            lockout_policy,
         }
     }
 
-    pub async fn sign_up(&self, email: String, password: String, username: String) -> Result<(String, String), UserProfileServiceError> {
+    pub async fn sign_up(&self, email: String, password: String, username: String, metadata: SessionMetadata) -> Result<(String, String), UserProfileServiceError> {
         User::validate_email(&email)?;
         User::validate_password(&password)?;
-        Profile::validate_username(&username)?;
+        let username_skeleton = Profile::validate_username(&username)?;
 
         if self.user_repository.find_one_by_email(&email).await.is_ok() {
             return Err(UserProfileServiceError::EmailAlreadyInUse);
         }
 
-        let (user, profile) = User::register(email, password, username)?;
+        if self.is_username_taken(&username_skeleton).await? {
+            return Err(UserProfileServiceError::UsernameAlreadyInUse);
+        }
+
+        let (mut user, profile) = User::register(email, password, username)?;
 
         let result: Result<_, UserProfileServiceError> = self.transaction_manager.transaction(|| async move {
             self.user_repository.insert(&user).await?;
-            self.profile_repository.insert(&profile).await?;
+            // A concurrent sign up can still claim the username first
+            self.profile_repository.insert(&profile)
+                .await
+                .map_err(|e| match e {
+                    RepositoryError::ConstraintConflict => UserProfileServiceError::UsernameAlreadyInUse,
+                    e => e.into()
+                })?;
+
+            let event = user.request_email_verification()?;
+            self.user_repository.update(&user).await?;
 
-            Ok((user, profile))
+            Ok((user, profile, event))
         }).await?;
 
+        let (user, profile, event) = result?;
 
-        let (user, profile) = result?;
+        // Handlers send mail, only dispatch once the transaction is committed
+        self.domain_event_dispatcher.dispatch(event).await?;
 
         let session_id = self.auth_connector
-            .create_session(user.get_id(), profile.get_id())
+            .create_session(user.get_id(), profile.get_id(), metadata)
             .await?;
 
         Ok((profile.get_id(), session_id))
     }
 
-    pub async fn sign_in(&self, email: &str, password: &str) -> Result<(String, String), UserProfileServiceError> {
+    // Same checks as sign up, without registering anything
+    pub async fn check_username_availability(&self, username: &str) -> Result<Availability, UserProfileServiceError> {
+        let username_skeleton = match Profile::validate_username(username) {
+            Ok(skeleton) => skeleton,
+            Err(ProfileDomainError::UsernameReserved) => return Ok(Availability::Reserved),
+            Err(_) => return Ok(Availability::Invalid)
+        };
+
+        match self.is_username_taken(&username_skeleton).await? {
+            true => Ok(Availability::Taken),
+            false => Ok(Availability::Available)
+        }
+    }
+
+    // Taken by a profile or still claimed by a recent username change
+    async fn is_username_taken(&self, username_skeleton: &str) -> Result<bool, UserProfileServiceError> {
+        let changed_after = OffsetDateTime::now_utc() - USERNAME_REDIRECT_GRACE_PERIOD;
+
+        let current = self.profile_repository.find_by_username_skeleton(username_skeleton).await;
+        let previous = self.profile_repository.find_by_previous_username_skeleton(username_skeleton, changed_after).await;
+
+        for result in [current, previous] {
+            match result {
+                Ok(_) => return Ok(true),
+                Err(RepositoryError::ResourceNotFound) => {}
+                Err(e) => return Err(e.into())
+            }
+        }
+
+        Ok(false)
+    }
+
+    pub async fn check_email_availability(&self, email: &str) -> Result<Availability, UserProfileServiceError> {
+        if User::validate_email(email).is_err() {
+            return Ok(Availability::Invalid);
+        }
+
+        match self.user_repository.find_one_by_email(&email.to_lowercase()).await {
+            Ok(_) => Ok(Availability::Taken),
+            Err(RepositoryError::ResourceNotFound) => Ok(Availability::Available),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    pub async fn sign_in(&self, email: &str, password: &str, metadata: SessionMetadata) -> Result<SignInOutcome, UserProfileServiceError> {
         User::validate_email(email)?;
         User::validate_password(password)?;
 
-        let user = self.user_repository.find_one_by_email(email).await?;
+        let (user, challenge_id) = self.transaction_manager.transaction(|| async {
+            let mut user = self.user_repository.find_one_by_email(email).await?;
+
+            let result = user.login(&password, &self.lockout_policy)
+                .and_then(|_| match self.verified_email_required_for_sign_in {
+                    true => user.ensure_email_verified(),
+                    false => Ok(())
+                });
+
+            let challenge_id = match result {
+                Ok(()) if user.is_totp_enabled() => Some(user.create_mfa_challenge()),
+                _ => None
+            };
+
+            // Failed attempts and lockouts are saved as well
+            self.user_repository.update(&user).await?;
+
+            Ok::<_, UserProfileServiceError>(result.map(|_| (user, challenge_id)))
+        }).await???;
+
+        if let Some(challenge_id) = challenge_id {
+            return Ok(SignInOutcome::MfaRequired { challenge_id });
+        }
+
+        let (profile_id, session_id) = self.create_session(&user, metadata).await?;
+
+        Ok(SignInOutcome::Session { profile_id, session_id })
+    }
+
+    pub async fn complete_mfa_sign_in(&self, challenge_id: &str, code: &str, metadata: SessionMetadata) -> Result<(String, String), UserProfileServiceError> {
+        let user = self.transaction_manager.transaction(|| async {
+            let mut user = self.find_by_mfa_challenge_id(challenge_id).await?;
+
+            let result = user.complete_mfa_challenge(challenge_id, &self.secret_cipher, code);
+
+            // Failed attempts are saved as well
+            self.user_repository.update(&user).await?;
+
+            Ok::<_, UserProfileServiceError>(result.map(|_| user))
+        }).await???;
 
-        user.login(&password)?;
+        self.create_session(&user, metadata).await
+    }
+
+    pub async fn complete_mfa_sign_in_with_recovery_code(&self, challenge_id: &str, recovery_code: &str, metadata: SessionMetadata) -> Result<(String, String), UserProfileServiceError> {
+        let (user, event) = self.transaction_manager.transaction(|| async {
+            let mut user = self.find_by_mfa_challenge_id(challenge_id).await?;
+
+            let result = user.complete_mfa_challenge_with_recovery_code(challenge_id, recovery_code);
+
+            // Failed attempts are saved as well
+            self.user_repository.update(&user).await?;
+
+            Ok::<_, UserProfileServiceError>(result.map(|event| (user, event)))
+        }).await???;
+
+        self.domain_event_dispatcher.dispatch(event).await?;
+
+        self.create_session(&user, metadata).await
+    }
+
+    async fn find_by_mfa_challenge_id(&self, challenge_id: &str) -> Result<User, UserProfileServiceError> {
+        self.user_repository.find_by_mfa_challenge_id(challenge_id)
+            .await
+            .map_err(|e| match e {
+                RepositoryError::ResourceNotFound => UserDomainError::InvalidMfaChallenge.into(),
+                e => e.into()
+            })
+    }
 
+    async fn create_session(&self, user: &User, metadata: SessionMetadata) -> Result<(String, String), UserProfileServiceError> {
         let profile = self.profile_repository.find_by_user_id(user.get_id()).await?;
 
         let session_id = self.auth_connector
-            .create_session(user.get_id(), profile.get_id())
+            .create_session(user.get_id(), profile.get_id(), metadata)
             .await?;
 
         Ok((profile.get_id(), session_id))
     }
 
     pub async fn request_reset_password(&self, email: &str, requester: String) -> Result<(), UserProfileServiceError> {
-        let token = self.transaction_manager.transaction(|| async {
+        let event = self.transaction_manager.transaction(|| async {
             let mut user = self.user_repository.find_one_by_email(&email).await?;
 
             let event = user.request_password_reset(requester)?;
 
             self.user_repository.update(&user).await?;
 
-            self.domain_event_dispatcher.dispatch(event).await?;
-
-            Ok::<_, UserProfileServiceError>(())
+            Ok::<_, UserProfileServiceError>(event)
         }).await??;
 
-        // todo send email with token
+        self.domain_event_dispatcher.dispatch(event).await?;
 
         Ok(())
     }
@@ -151,6 +309,191 @@ impl UserProfileService {
             Ok::<(), UserProfileServiceError>(())
         }).await??;
 
+        // Whoever had access before the reset shouldn't keep it
+        self.auth_connector
+            .revoke_all_sessions_for_user(user.get_id())
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn verify_email(&self, token: &str) -> Result<(), UserProfileServiceError> {
+        self.transaction_manager.transaction(|| async {
+            let mut user = self.user_repository.find_by_email_verification_token(token).await?;
+
+            user.verify_email(token)?;
+
+            self.user_repository.update(&user).await?;
+
+            Ok::<_, UserProfileServiceError>(())
+        }).await??;
+
+        Ok(())
+    }
+
+    pub async fn resend_email_verification(&self, user_id: &str) -> Result<(), UserProfileServiceError> {
+        let event = self.transaction_manager.transaction(|| async {
+            let mut user = self.user_repository.find_by_id(user_id).await?;
+
+            let event = user.request_email_verification()?;
+
+            self.user_repository.update(&user).await?;
+
+            Ok::<_, UserProfileServiceError>(event)
+        }).await??;
+
+        self.domain_event_dispatcher.dispatch(event).await?;
+
+        Ok(())
+    }
+
+    pub async fn change_password(&self, user_id: &str, current_password: &str, new_password: &str) -> Result<(), UserProfileServiceError> {
+        User::validate_password(new_password)?;
+
+        let event = self.transaction_manager.transaction(|| async {
+            let mut user = self.user_repository.find_by_id(user_id).await?;
+
+            let event = user.change_password(current_password, new_password)?;
+
+            self.user_repository.update(&user).await?;
+
+            Ok::<_, UserProfileServiceError>(event)
+        }).await??;
+
+        self.domain_event_dispatcher.dispatch(event).await?;
+
+        Ok(())
+    }
+
+    pub async fn request_email_change(&self, user_id: &str, password: &str, new_email: &str) -> Result<(), UserProfileServiceError> {
+        let new_email = new_email.to_lowercase();
+        User::validate_email(&new_email)?;
+
+        if self.user_repository.find_one_by_email(&new_email).await.is_ok() {
+            return Err(UserProfileServiceError::EmailAlreadyInUse);
+        }
+
+        let event = self.transaction_manager.transaction(|| async {
+            let mut user = self.user_repository.find_by_id(user_id).await?;
+
+            let event = user.request_email_change(password, &new_email)?;
+
+            self.user_repository.update(&user).await?;
+
+            Ok::<_, UserProfileServiceError>(event)
+        }).await??;
+
+        self.domain_event_dispatcher.dispatch(event).await?;
+
+        Ok(())
+    }
+
+    pub async fn confirm_email_change(&self, token: &str) -> Result<(), UserProfileServiceError> {
+        self.transaction_manager.transaction(|| async {
+            let mut user = self.user_repository.find_by_email_change_token(token).await?;
+
+            // The address could have been taken since the change was requested
+            if let Some(request) = user.email_change_request() {
+                if self.user_repository.find_one_by_email(request.new_email()).await.is_ok() {
+                    return Err(UserProfileServiceError::EmailAlreadyInUse);
+                }
+            }
+
+            user.confirm_email_change(token)?;
+
+            self.user_repository.update(&user).await?;
+
+            Ok::<_, UserProfileServiceError>(())
+        }).await??;
+
+        Ok(())
+    }
+
+    pub async fn enroll_totp(&self, user_id: &str) -> Result<TotpEnrollment, UserProfileServiceError> {
+        let enrollment = self.transaction_manager.transaction(|| async {
+            let mut user = self.user_repository.find_by_id(user_id).await?;
+
+            let secret = user.enroll_totp(&self.secret_cipher)?;
+
+            self.user_repository.update(&user).await?;
+
+            Ok::<_, UserProfileServiceError>(TotpEnrollment {
+                secret: totp::encode_secret(&secret),
+                provisioning_uri: totp::provisioning_uri(&secret, user.get_email()),
+            })
+        }).await??;
+
+        Ok(enrollment)
+    }
+
+    pub async fn confirm_totp(&self, user_id: &str, code: &str) -> Result<Vec<String>, UserProfileServiceError> {
+        let recovery_codes = self.transaction_manager.transaction(|| async {
+            let mut user = self.user_repository.find_by_id(user_id).await?;
+
+            let recovery_codes = user.confirm_totp(&self.secret_cipher, code)?;
+
+            self.user_repository.update(&user).await?;
+
+            Ok::<_, UserProfileServiceError>(recovery_codes)
+        }).await??;
+
+        Ok(recovery_codes)
+    }
+
+    pub async fn regenerate_recovery_codes(&self, user_id: &str, password: &str) -> Result<Vec<String>, UserProfileServiceError> {
+        let recovery_codes = self.transaction_manager.transaction(|| async {
+            let mut user = self.user_repository.find_by_id(user_id).await?;
+
+            let recovery_codes = user.regenerate_recovery_codes(password)?;
+
+            self.user_repository.update(&user).await?;
+
+            Ok::<_, UserProfileServiceError>(recovery_codes)
+        }).await??;
+
+        Ok(recovery_codes)
+    }
+
+    pub async fn disable_totp(&self, user_id: &str, password: &str, code: &str) -> Result<(), UserProfileServiceError> {
+        self.transaction_manager.transaction(|| async {
+            let mut user = self.user_repository.find_by_id(user_id).await?;
+
+            user.disable_totp(&self.secret_cipher, password, code)?;
+
+            self.user_repository.update(&user).await?;
+
+            Ok::<_, UserProfileServiceError>(())
+        }).await??;
+
         Ok(())
     }
-}
\ No newline at end of file
+
+    pub async fn sign_out(&self, session_id: String) -> Result<(), UserProfileServiceError> {
+        self.auth_connector
+            .revoke_session(session_id)
+            .await
+            .map_err(|e| e.into())
+    }
+
+    pub async fn sign_out_everywhere(&self, user_id: String) -> Result<(), UserProfileServiceError> {
+        self.auth_connector
+            .revoke_all_sessions_for_user(user_id)
+            .await
+            .map_err(|e| e.into())
+    }
+
+    pub async fn list_sessions(&self, user_id: String, current_session_token: Option<String>) -> Result<Vec<SessionInfo>, UserProfileServiceError> {
+        self.auth_connector
+            .list_sessions(user_id, current_session_token)
+            .await
+            .map_err(|e| e.into())
+    }
+
+    // The auth service only revokes the session if it belongs to the user
+    pub async fn revoke_session(&self, user_id: String, session_id: String) -> Result<(), UserProfileServiceError> {
+        self.auth_connector
+            .revoke_session_by_id(user_id, session_id)
+            .await
+            .map_err(|e| e.into())
+    }
+}
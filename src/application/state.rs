@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use deadpool_postgres::{Config, ManagerConfig, RecyclingMethod, Runtime};
 use figure_lib::queue::integration::domain_event_dispatcher::DomainEventDispatcher;
@@ -8,28 +8,40 @@ use figure_lib::rdbs::postgres::tokio_postgres::outbox::TokioPostgresOutbox;
 use figure_lib::rdbs::transaction::postgres_transaction::{TransactionBackend, TransactionManager};
 use tokio::task;
 use tokio_postgres::NoTls;
-use tracing::log::info;
+use tracing::log::{info, warn};
 use url::Url;
 
 use crate::application::connectors::auth_connector::AuthConnector;
+use crate::application::connectors::mailer::Mailer;
+use crate::application::connectors::media_storage::MediaStorage;
 use crate::application::domain_event_dispatcher::{DomainEvent, DomainEventDiscriminants};
-use crate::application::domain_event_handlers::user_created::password_reset_requested;
+use crate::application::domain_event_handlers::profile_events::{profile_followed, profile_unfollowed, username_changed};
+use crate::application::domain_event_handlers::user_created::{email_change_requested, password_changed, password_reset_requested, recovery_code_used, user_email_verification_requested};
 use crate::application::environment::Environment;
 use crate::application::migration_runner_trait::MigrationRunner;
 use crate::application::repository_traits::read::profile_repository::ProfileRepository;
 use crate::application::repository_traits::read::user_repository::UserRepository;
 use crate::application::services::profile_service::ProfileService;
 use crate::application::services::user_service::UserProfileService;
+use crate::domain::profile::profile::ReservedUsernames;
+use crate::domain::user::user::LockoutPolicy;
+use crate::infrastructure::database::repositories::block_repository::PostgresBlockRepository;
+use crate::infrastructure::database::repositories::follow_repository::PostgresFollowRepository;
 use crate::infrastructure::database::repositories::profile_repository::PostgresProfileRepository;
 use crate::infrastructure::database::repositories::user_repository::TokioPostgresUserRepository;
 use crate::infrastructure::database::TokioPostgresMigrationRunner;
-use crate::infrastructure::GrpcAuthConnector;
+use crate::infrastructure::http::middleware::rate_limit_layer::RateLimiter;
+use crate::infrastructure::http::middleware::session_layer::SessionHeaderVerifier;
+use crate::infrastructure::secret_cipher::SecretCipher;
+use crate::infrastructure::{FileMailer, GrpcAuthConnector, InMemoryMailer, LocalMediaStorage, S3MediaStorage, SmtpMailer};
 
 pub struct ServerState {
     pub migration_runner: Box<dyn MigrationRunner>,
     pub domain_dispatcher: Arc<DomainEventDispatcher<DomainEventDiscriminants, DomainEvent, Arc<DomainEventHandlerState>>>,
     pub user_service: UserProfileService,
     pub profile_service: ProfileService,
+    pub rate_limiter: RateLimiter,
+    pub session_header_verifier: SessionHeaderVerifier,
 
     pub domain: String,
 }
@@ -42,9 +54,19 @@ impl ServerState {
                    Arc<DomainEventHandlerState>>>,
                user_service: UserProfileService,
                profile_service: ProfileService,
+               rate_limiter: RateLimiter,
+               session_header_verifier: SessionHeaderVerifier,
                domain: String)
                -> Self {
-        Self { migration_runner, domain_dispatcher, user_service, profile_service, domain }
+        Self {
+            migration_runner,
+            domain_dispatcher,
+            user_service,
+            profile_service,
+            rate_limiter,
+            session_header_verifier,
+            domain,
+        }
     }
 }
 
@@ -54,6 +76,10 @@ pub struct DomainEventHandlerState {
     pub profile_repository: Box<dyn ProfileRepository>,
     pub outbox_repository: Box<dyn Outbox>,
     pub auth_connector: Box<dyn AuthConnector>,
+    pub mailer: Box<dyn Mailer>,
+
+    // Base url used in links sent to users
+    pub origin: String,
 }
 
 pub async fn create_state(env: &Environment) -> Result<Arc<ServerState>, anyhow::Error> {
@@ -84,18 +110,47 @@ pub async fn create_state(env: &Environment) -> Result<Arc<ServerState>, anyhow:
             })
     });
 
+    let redis_url = env.redis_url.clone();
+    let redis_future = task::spawn(async move {
+        let redis_url = redis_url?;
+        let time = Instant::now();
+
+        let result = match redis::Client::open(redis_url) {
+            Ok(client) => client.get_connection_manager().await,
+            Err(e) => Err(e)
+        };
+
+        match result {
+            Ok(connection) => {
+                info!("Connected to Redis in {}ms...", time.elapsed().as_millis());
+                Some(connection)
+            }
+            Err(e) => {
+                warn!("Couldn't connect to Redis, rate limiting in memory: {}", e);
+                None
+            }
+        }
+    });
+
+    let mailer = create_mailer(env)?;
+
+    load_reserved_usernames(env).await?;
+
     let domain = Url::parse(&env.origin)?.host_str().unwrap().to_string();
     info!("Domain parsed from origin: {}", domain);
 
     info!("Waiting for connections...");
     let db_pool = db_pool_future.await?;
     let auth_connector = auth_connector_future.await??;
+    let redis_connection = redis_future.await?;
 
     // Initialize repositories
     let migration_runner = Box::new(TokioPostgresMigrationRunner::new(db_pool.clone()));
     let transaction_starter = TransactionManager::new(TransactionBackend::PostgresTokio(db_pool.clone()));
     let user_repository = TokioPostgresUserRepository::new(db_pool.clone());
-    let profile_repository = PostgresProfileRepository::new(db_pool);
+    let profile_repository = PostgresProfileRepository::new(db_pool.clone());
+    let follow_repository = PostgresFollowRepository::new(db_pool.clone());
+    let block_repository = PostgresBlockRepository::new(db_pool);
     let outbox_repository = TokioPostgresOutbox::new();
 
     let domain_event_dispatcher: DomainEventDispatcher<DomainEventDiscriminants, DomainEvent, _> =
@@ -105,21 +160,124 @@ pub async fn create_state(env: &Environment) -> Result<Arc<ServerState>, anyhow:
             profile_repository: Box::new(profile_repository.clone()),
             outbox_repository: Box::new(outbox_repository.clone()),
             auth_connector: Box::new(auth_connector.clone()),
+            mailer,
+            origin: env.origin.clone(),
         }))
-            .register(password_reset_requested);
+            .register(password_reset_requested)
+            .register(user_email_verification_requested)
+            .register(password_changed)
+            .register(email_change_requested)
+            .register(recovery_code_used)
+            .register(username_changed)
+            .register(profile_followed)
+            .register(profile_unfollowed);
 
     let domain_event_dispatcher = Arc::new(domain_event_dispatcher);
 
     // Initialize services
     let user_service = UserProfileService::new(
         transaction_starter.clone(), domain_event_dispatcher.clone(),
-        Box::new(user_repository),
+        Box::new(user_repository.clone()),
         Box::new(profile_repository.clone()),
         Box::new(outbox_repository),
-        Box::new(auth_connector));
+        Box::new(auth_connector),
+        env.verified_email_required_for_sign_in,
+        SecretCipher::new(&env.totp_encryption_key),
+        LockoutPolicy {
+            threshold: env.sign_in_lockout_threshold,
+            lock_duration: Duration::from_secs(env.sign_in_lockout_seconds),
+        });
 
-    let profile_service = ProfileService::new(Box::new(profile_repository));
+    let profile_service = ProfileService::new(
+        transaction_starter.clone(), domain_event_dispatcher.clone(),
+        Box::new(profile_repository),
+        Box::new(user_repository),
+        Box::new(follow_repository),
+        Box::new(block_repository),
+        env.verified_email_required_for_profile_update,
+        create_media_storage(env)?);
+
+    let rate_limiter = RateLimiter::new(redis_connection);
+
+    let session_header_verifier = match (env.trust_session_headers, &env.session_header_secret) {
+        (true, _) => {
+            warn!("Trusting session headers without verifying them, only use this for local development!");
+            SessionHeaderVerifier::Trusted
+        }
+        (false, Some(secret)) => SessionHeaderVerifier::signed(secret),
+        (false, None) => return Err(anyhow::Error::msg("No SESSION_HEADER_SECRET env found"))
+    };
 
     // Resulting state
-    Ok(Arc::new(ServerState::new(migration_runner, domain_event_dispatcher, user_service, profile_service, domain)))
+    Ok(Arc::new(ServerState::new(migration_runner, domain_event_dispatcher, user_service, profile_service,
+                                 rate_limiter, session_header_verifier, domain)))
+}
+
+fn create_mailer(env: &Environment) -> Result<Box<dyn Mailer>, anyhow::Error> {
+    info!("Using {} mail transport...", env.mail_transport);
+
+    let mailer: Box<dyn Mailer> = match env.mail_transport.as_str() {
+        "smtp" => {
+            let host = env.smtp_host.as_deref()
+                .ok_or_else(|| anyhow::Error::msg("No SMTP_HOST env found"))?;
+
+            Box::new(SmtpMailer::new(
+                host,
+                env.smtp_port.unwrap_or(587),
+                env.smtp_username.clone().unwrap_or_default(),
+                env.smtp_password.clone().unwrap_or_default(),
+                &env.mail_from)?)
+        }
+        "file" => Box::new(FileMailer::new(env.mail_directory.clone().into())),
+        "memory" => Box::new(InMemoryMailer::new()),
+        other => return Err(anyhow::Error::msg(format!("Unknown MAIL_TRANSPORT: {other}")))
+    };
+
+    Ok(mailer)
+}
+
+fn create_media_storage(env: &Environment) -> Result<Box<dyn MediaStorage>, anyhow::Error> {
+    info!("Using {} media storage...", env.media_storage);
+
+    let media_storage: Box<dyn MediaStorage> = match env.media_storage.as_str() {
+        "s3" => {
+            let bucket = env.s3_bucket.clone()
+                .ok_or_else(|| anyhow::Error::msg("No S3_BUCKET env found"))?;
+            let access_key_id = env.s3_access_key_id.clone()
+                .ok_or_else(|| anyhow::Error::msg("No S3_ACCESS_KEY_ID env found"))?;
+            let secret_access_key = env.s3_secret_access_key.clone()
+                .ok_or_else(|| anyhow::Error::msg("No S3_SECRET_ACCESS_KEY env found"))?;
+
+            Box::new(S3MediaStorage::new(
+                bucket,
+                env.s3_region.clone().unwrap_or_else(|| "us-east-1".to_string()),
+                env.s3_endpoint.clone(),
+                access_key_id,
+                secret_access_key,
+                env.media_public_url.clone()))
+        }
+        "local" => Box::new(LocalMediaStorage::new(env.media_directory.clone().into(), env.media_public_url.clone())),
+        other => return Err(anyhow::Error::msg(format!("Unknown MEDIA_STORAGE: {other}")))
+    };
+
+    Ok(media_storage)
+}
+
+async fn load_reserved_usernames(env: &Environment) -> Result<(), anyhow::Error> {
+    let blocked_substrings = match &env.username_blocklist_file {
+        Some(path) => tokio::fs::read_to_string(path)
+            .await?
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.to_string())
+            .collect(),
+        None => Vec::new()
+    };
+
+    info!("Loaded {} reserved usernames and {} blocked substrings", env.reserved_usernames.len(), blocked_substrings.len());
+
+    ReservedUsernames::new(env.reserved_usernames.clone(), blocked_substrings).install();
+
+    Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,67 @@
+pub use follow::Follow;
+pub use follow::FollowDomainError;
+
+pub mod follow {
+    use thiserror::Error;
+    use time::OffsetDateTime;
+
+    use crate::application::domain_event_dispatcher::{DomainEvent, ProfileFollowed, ProfileUnfollowed};
+
+    pub struct Follow {
+        follower_id: String,
+        followee_id: String,
+        created_at: OffsetDateTime,
+    }
+
+    #[derive(Debug, Error)]
+    pub enum FollowDomainError {
+        #[error("cannot-follow-self")]
+        CannotFollowSelf,
+    }
+
+    impl Follow {
+        pub fn new(follower_id: String, followee_id: String, created_at: OffsetDateTime) -> Self {
+            Self {
+                follower_id,
+                followee_id,
+                created_at,
+            }
+        }
+
+        pub fn follow(follower_id: String, followee_id: String) -> Result<(Self, DomainEvent), FollowDomainError> {
+            if follower_id == followee_id {
+                return Err(FollowDomainError::CannotFollowSelf);
+            }
+
+            let follow = Self::new(follower_id, followee_id, OffsetDateTime::now_utc());
+
+            let event = ProfileFollowed {
+                follower_id: follow.follower_id.clone(),
+                followee_id: follow.followee_id.clone(),
+                datetime: follow.created_at,
+            }.into();
+
+            Ok((follow, event))
+        }
+
+        pub fn unfollow(self) -> DomainEvent {
+            ProfileUnfollowed {
+                follower_id: self.follower_id,
+                followee_id: self.followee_id,
+                datetime: OffsetDateTime::now_utc(),
+            }.into()
+        }
+
+        pub fn follower_id(&self) -> &str {
+            &self.follower_id
+        }
+
+        pub fn followee_id(&self) -> &str {
+            &self.followee_id
+        }
+
+        pub fn created_at(&self) -> OffsetDateTime {
+            self.created_at
+        }
+    }
+}
@@ -1,3 +1,4 @@
+pub use follow::Follow;
 pub use profile::Profile;
 pub use user::User;
 
@@ -5,3 +6,4 @@ pub mod user;
 
 pub mod profile;
 
+pub mod follow;
@@ -1,13 +1,28 @@
+pub use profile::Audience;
 pub use profile::Profile;
 pub use profile::ProfileDomainError;
+pub use profile::Visibility;
 
 pub mod profile {
+    use std::collections::HashSet;
+    use std::str::FromStr;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+
     use lazy_static::lazy_static;
     use regex::Regex;
     use thiserror::Error;
+    use time::OffsetDateTime;
     use unicode_segmentation::UnicodeSegmentation;
     use uuid::Uuid;
 
+    use crate::application::domain_event_dispatcher::{DomainEvent, UsernameChanged};
+
+    // Minimum time between two username changes
+    pub const USERNAME_CHANGE_COOLDOWN: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+    // How long an old username keeps redirecting and can't be claimed by someone else
+    pub const USERNAME_REDIRECT_GRACE_PERIOD: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
     pub struct Profile {
         pub id: String,
         pub username: String,
@@ -16,12 +31,44 @@ pub mod profile {
         pub banner: Option<String>,
         pub profile_picture: Option<String>,
         pub user_id: String,
+        pub username_changed_at: Option<OffsetDateTime>,
+        pub follower_count: i64,
+        pub following_count: i64,
+        pub visibility: Visibility,
+        pub display_name_visibility: Visibility,
+        pub bio_visibility: Visibility,
+    }
+
+    // Who can see a profile or one of its fields
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+    pub enum Visibility {
+        #[default]
+        Public,
+        SignedIn,
+        Followers,
+    }
+
+    // Relation of the viewer to a profile, from least to most access
+    #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+    pub enum Audience {
+        Anonymous,
+        SignedIn,
+        Follower,
+        Owner,
     }
 
     #[derive(Debug, Error)]
     pub enum ProfileDomainError {
         #[error("invalid-username")]
         InvalidUsername,
+        #[error("username-unchanged")]
+        UsernameUnchanged,
+        #[error("username-change-cooldown")]
+        UsernameChangeCooldown,
+        #[error("username-reserved")]
+        UsernameReserved,
+        #[error("invalid-visibility")]
+        InvalidVisibility,
     }
 
     lazy_static! {
@@ -29,6 +76,17 @@ pub mod profile {
         Regex::new("^[a-zA-Z0-9]+-*[a-zA-Z0-9]+?$").unwrap();
     }
 
+    // Set once at startup, no usernames are reserved until then
+    static RESERVED_USERNAMES: OnceLock<ReservedUsernames> = OnceLock::new();
+
+    // Usernames that can't be registered, compared by skeleton so look-alikes are caught as well
+    pub struct ReservedUsernames {
+        // Exact matches
+        reserved: HashSet<String>,
+        // Rejected anywhere inside the username, dashes are ignored
+        blocked_substrings: Vec<String>,
+    }
+
     impl Profile {
         pub fn register(username: String, user_id: String) -> Result<Self, ProfileDomainError> {
             Self::validate_username(&username)?;
@@ -43,20 +101,104 @@ pub mod profile {
                 banner: None,
                 profile_picture: None,
                 user_id,
+                username_changed_at: None,
+                follower_count: 0,
+                following_count: 0,
+                visibility: Visibility::Public,
+                display_name_visibility: Visibility::Public,
+                bio_visibility: Visibility::Public,
             })
         }
 
+        // Changing only the case of the username doesn't free up the old one, the skeleton stays the same
+        pub fn change_username(&mut self, new_username: String) -> Result<DomainEvent, ProfileDomainError> {
+            Self::validate_username(&new_username)?;
+
+            if new_username == self.username {
+                return Err(ProfileDomainError::UsernameUnchanged);
+            }
+
+            let datetime_now = OffsetDateTime::now_utc();
+
+            if let Some(changed_at) = self.username_changed_at {
+                if changed_at + USERNAME_CHANGE_COOLDOWN > datetime_now {
+                    return Err(ProfileDomainError::UsernameChangeCooldown);
+                }
+            }
+
+            let old_username = std::mem::replace(&mut self.username, new_username);
+            self.username_changed_at = Some(datetime_now);
+
+            Ok(UsernameChanged {
+                profile_id: self.id.clone(),
+                old_username,
+                new_username: self.username.clone(),
+                datetime: datetime_now,
+            }.into())
+        }
+
         // Valid username test
         // (alphanumerical, optionally a dash surrounded by alphanumerical characters, 15 character limit)
-        // todo unit tests
-        pub fn validate_username(username: &str) -> Result<(), ProfileDomainError> {
+        // Returns the skeleton of a valid username
+        pub fn validate_username(username: &str) -> Result<String, ProfileDomainError> {
             let username_count = username.graphemes(true).count();
 
             if !USERNAME_REGEX.is_match(username) || !(3..=15).contains(&username_count) {
                 return Err(ProfileDomainError::InvalidUsername);
             }
 
-            Ok(())
+            if Self::is_reserved_username(username) {
+                return Err(ProfileDomainError::UsernameReserved);
+            }
+
+            Ok(Self::username_skeleton(username))
+        }
+
+        // Canonical form used for uniqueness, usernames that look alike share a skeleton (Bob, bob, B0b).
+        // Kept in sync with the backfill in the V8 migration
+        pub fn username_skeleton(username: &str) -> String {
+            username
+                .to_lowercase()
+                .replace("rn", "m")
+                .chars()
+                .map(|c| match c {
+                    '0' => 'o',
+                    '1' | 'i' => 'l',
+                    '5' => 's',
+                    c => c
+                })
+                .collect()
+        }
+
+        pub fn is_reserved_username(username: &str) -> bool {
+            RESERVED_USERNAMES.get()
+                .map(|reserved_usernames| reserved_usernames.contains(username))
+                .unwrap_or(false)
+        }
+
+        pub fn is_visible_to(&self, audience: Audience) -> bool {
+            self.visibility.allows(audience)
+        }
+
+        // Whether the viewer has to be a follower for any part of the profile
+        pub fn has_followers_only_content(&self) -> bool {
+            [self.visibility, self.display_name_visibility, self.bio_visibility].contains(&Visibility::Followers)
+        }
+
+        // Clears the fields the audience isn't allowed to see,
+        // nothing but the username and images is left when the profile itself isn't visible
+        pub fn with_fields_visible_to(mut self, audience: Audience) -> Self {
+            let profile_visible = self.is_visible_to(audience);
+
+            if !profile_visible || !self.display_name_visibility.allows(audience) {
+                self.display_name = None;
+            }
+
+            if !profile_visible || !self.bio_visibility.allows(audience) {
+                self.bio = None;
+            }
+
+            self
         }
 
         pub fn get_id(&self) -> String {
@@ -67,8 +209,201 @@ pub mod profile {
             &self.username
         }
 
+        pub fn get_username_skeleton(&self) -> String {
+            Self::username_skeleton(&self.username)
+        }
+
         pub fn get_user_id(&self) -> String {
             self.user_id.clone()
         }
     }
-}
\ No newline at end of file
+
+    impl Visibility {
+        pub fn allows(self, audience: Audience) -> bool {
+            let required = match self {
+                Visibility::Public => Audience::Anonymous,
+                Visibility::SignedIn => Audience::SignedIn,
+                Visibility::Followers => Audience::Follower,
+            };
+
+            audience >= required
+        }
+
+        pub fn as_str(self) -> &'static str {
+            match self {
+                Visibility::Public => "public",
+                Visibility::SignedIn => "signed-in",
+                Visibility::Followers => "followers",
+            }
+        }
+    }
+
+    impl FromStr for Visibility {
+        type Err = ProfileDomainError;
+
+        fn from_str(value: &str) -> Result<Self, Self::Err> {
+            match value {
+                "public" => Ok(Visibility::Public),
+                "signed-in" => Ok(Visibility::SignedIn),
+                "followers" => Ok(Visibility::Followers),
+                _ => Err(ProfileDomainError::InvalidVisibility)
+            }
+        }
+    }
+
+    impl ReservedUsernames {
+        pub fn new(reserved: Vec<String>, blocked_substrings: Vec<String>) -> Self {
+            Self {
+                reserved: reserved.iter()
+                    .map(|username| Profile::username_skeleton(username.trim()))
+                    .filter(|skeleton| !skeleton.is_empty())
+                    .collect(),
+                blocked_substrings: blocked_substrings.iter()
+                    .map(|substring| Profile::username_skeleton(substring.trim()).replace('-', ""))
+                    .filter(|skeleton| !skeleton.is_empty())
+                    .collect(),
+            }
+        }
+
+        // Only the first call has an effect
+        pub fn install(self) {
+            let _ = RESERVED_USERNAMES.set(self);
+        }
+
+        pub fn contains(&self, username: &str) -> bool {
+            let skeleton = Profile::username_skeleton(username);
+            let skeleton_without_dashes = skeleton.replace('-', "");
+
+            self.reserved.contains(&skeleton)
+                || self.blocked_substrings.iter().any(|substring| skeleton_without_dashes.contains(substring))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        // Results of translate(replace(lower(username), 'rn', 'm'), '01i5', 'olls') in Postgres
+        const SQL_SKELETONS: [(&str, &str); 8] = [
+            ("bob", "bob"),
+            ("Bob", "bob"),
+            ("B0B", "bob"),
+            ("barn", "bam"),
+            ("BaRN", "bam"),
+            ("rnrn", "mm"),
+            ("rrnn", "rmn"),
+            ("Alice-15", "allce-ls"),
+        ];
+
+        #[test]
+        fn skeleton_matches_the_v8_backfill() {
+            let migration = include_str!("../infrastructure/database/migrations/V8__username_skeleton.sql");
+            assert!(migration.contains("translate(replace(lower(username), 'rn', 'm'), '01i5', 'olls')"));
+
+            for (username, skeleton) in SQL_SKELETONS {
+                assert_eq!(Profile::username_skeleton(username), skeleton, "for {username}");
+            }
+        }
+
+        #[test]
+        fn look_alike_usernames_share_a_skeleton() {
+            assert_eq!(Profile::username_skeleton("modern"), Profile::username_skeleton("rnodern"));
+            assert_eq!(Profile::username_skeleton("sil"), Profile::username_skeleton("511"));
+            assert_ne!(Profile::username_skeleton("bob"), Profile::username_skeleton("rob"));
+        }
+
+        #[test]
+        fn validates_usernames() {
+            assert_eq!(Profile::validate_username("Bob").unwrap(), "bob");
+            assert!(Profile::validate_username("bob-smith").is_ok());
+
+            for username in ["bo", "sixteen-letters1", "bob_smith", "-bob", "bob-", "bob smith"] {
+                assert!(matches!(Profile::validate_username(username), Err(ProfileDomainError::InvalidUsername)), "for {username}");
+            }
+        }
+
+        #[test]
+        fn reserved_usernames_match_look_alikes_and_substrings() {
+            let reserved_usernames = ReservedUsernames::new(vec!["admin".to_string()], vec!["staff".to_string()]);
+
+            assert!(reserved_usernames.contains("Adm1n"));
+            assert!(reserved_usernames.contains("st-aff-member"));
+            assert!(!reserved_usernames.contains("administrator"));
+        }
+
+        fn profile_with(visibility: Visibility, display_name_visibility: Visibility, bio_visibility: Visibility) -> Profile {
+            let mut profile = Profile::register("bob".to_string(), "user".to_string()).unwrap();
+
+            profile.display_name = Some("Bob".to_string());
+            profile.bio = Some("Hello".to_string());
+            profile.visibility = visibility;
+            profile.display_name_visibility = display_name_visibility;
+            profile.bio_visibility = bio_visibility;
+
+            profile
+        }
+
+        #[test]
+        fn audiences_are_ordered_by_access() {
+            assert!(Audience::Anonymous < Audience::SignedIn);
+            assert!(Audience::SignedIn < Audience::Follower);
+            assert!(Audience::Follower < Audience::Owner);
+        }
+
+        #[test]
+        fn visibility_allows_its_audience_and_above() {
+            let audiences = [Audience::Anonymous, Audience::SignedIn, Audience::Follower, Audience::Owner];
+            let expected = [
+                (Visibility::Public, [true, true, true, true]),
+                (Visibility::SignedIn, [false, true, true, true]),
+                (Visibility::Followers, [false, false, true, true]),
+            ];
+
+            for (visibility, allowed) in expected {
+                for (audience, allowed) in audiences.into_iter().zip(allowed) {
+                    assert_eq!(visibility.allows(audience), allowed, "for {visibility:?} and {audience:?}");
+                }
+            }
+        }
+
+        #[test]
+        fn visibility_round_trips_through_strings() {
+            for visibility in [Visibility::Public, Visibility::SignedIn, Visibility::Followers] {
+                assert_eq!(visibility.as_str().parse::<Visibility>().unwrap(), visibility);
+            }
+
+            assert!(matches!("private".parse::<Visibility>(), Err(ProfileDomainError::InvalidVisibility)));
+        }
+
+        #[test]
+        fn hides_fields_the_audience_cannot_see() {
+            let visible_to = |audience| profile_with(Visibility::Public, Visibility::SignedIn, Visibility::Followers)
+                .with_fields_visible_to(audience);
+
+            let anonymous = visible_to(Audience::Anonymous);
+            assert_eq!(anonymous.display_name, None);
+            assert_eq!(anonymous.bio, None);
+
+            let signed_in = visible_to(Audience::SignedIn);
+            assert_eq!(signed_in.display_name.as_deref(), Some("Bob"));
+            assert_eq!(signed_in.bio, None);
+
+            let follower = visible_to(Audience::Follower);
+            assert_eq!(follower.display_name.as_deref(), Some("Bob"));
+            assert_eq!(follower.bio.as_deref(), Some("Hello"));
+        }
+
+        #[test]
+        fn hides_all_fields_when_the_profile_is_not_visible() {
+            let profile = profile_with(Visibility::Followers, Visibility::Public, Visibility::Public);
+
+            assert!(!profile.is_visible_to(Audience::SignedIn));
+            assert!(profile.has_followers_only_content());
+
+            let signed_in = profile.with_fields_visible_to(Audience::SignedIn);
+            assert_eq!(signed_in.username, "bob");
+            assert_eq!(signed_in.display_name, None);
+            assert_eq!(signed_in.bio, None);
+        }
+    }
+}
@@ -9,6 +9,7 @@ pub mod user {
     use argon2::password_hash::{Error, SaltString};
     use error_conversion_macro::ErrorEnum;
     use lazy_static::lazy_static;
+    use rand::Rng;
     use rand_chacha::ChaCha20Rng;
     use rand_core::{OsRng, RngCore, SeedableRng};
     use regex::Regex;
@@ -17,17 +18,51 @@ pub mod user {
     use unicode_segmentation::UnicodeSegmentation;
     use uuid::Uuid;
 
-    use crate::application::domain_event_dispatcher::{DomainEvent, PasswordResetRequested};
+    use crate::application::domain_event_dispatcher::{DomainEvent, EmailChangeRequested, PasswordChanged, PasswordResetRequested, RecoveryCodeUsed, UserEmailVerificationRequested};
     use crate::domain::Profile;
     use crate::domain::profile::ProfileDomainError;
+    use crate::infrastructure::secret_cipher::SecretCipher;
     use crate::infrastructure::secure_hasher::ARGON2_HASHER;
+    use crate::infrastructure::totp;
 
     pub struct User {
         id: String,
         email: String,
         password: String,
         role: String,
+        email_verified: bool,
+        failed_sign_in_attempts: i32,
+        locked_until: Option<OffsetDateTime>,
         password_reset_requests: Vec<ResetPasswordRequest>,
+        email_verification_requests: Vec<EmailVerificationRequest>,
+        email_change_request: Option<EmailChangeRequest>,
+        totp: Option<TotpCredential>,
+        mfa_challenges: Vec<MfaChallenge>,
+        recovery_codes: Vec<RecoveryCode>,
+    }
+
+    // Failed sign-ins past the threshold lock the account, each further failure doubles the lock
+    pub struct LockoutPolicy {
+        pub threshold: i32,
+        pub lock_duration: Duration,
+    }
+
+    // Single-use fallback for the second factor, only the argon2 hash is kept
+    pub struct RecoveryCode {
+        hash: String,
+    }
+
+    // The secret is stored encrypted, it only counts as a second factor once enabled
+    pub struct TotpCredential {
+        encrypted_secret: Vec<u8>,
+        enabled: bool,
+        last_used_step: Option<i64>,
+    }
+
+    pub struct MfaChallenge {
+        id: String,
+        datetime: OffsetDateTime,
+        failed_attempts: i32,
     }
 
     pub struct ResetPasswordRequest {
@@ -35,6 +70,17 @@ pub mod user {
         datetime: OffsetDateTime,
     }
 
+    pub struct EmailVerificationRequest {
+        token: String,
+        datetime: OffsetDateTime,
+    }
+
+    pub struct EmailChangeRequest {
+        new_email: String,
+        token: String,
+        datetime: OffsetDateTime,
+    }
+
     #[derive(Debug, Error, ErrorEnum)]
     pub enum UserDomainError {
         #[error(transparent)]
@@ -56,17 +102,73 @@ pub mod user {
         InvalidPasswordResetToken,
         #[error("password-reset-token-expired")]
         PasswordResetTokenExpired,
+        #[error("email-not-verified")]
+        EmailNotVerified,
+        #[error("email-already-verified")]
+        EmailAlreadyVerified,
+        #[error("too-many-email-verifications-requested")]
+        TooManyEmailVerificationsRequested,
+        #[error("invalid-email-verification-token")]
+        InvalidEmailVerificationToken,
+        #[error("email-verification-token-expired")]
+        EmailVerificationTokenExpired,
+        #[error("email-unchanged")]
+        EmailUnchanged,
+        #[error("invalid-email-change-token")]
+        InvalidEmailChangeToken,
+        #[error("email-change-token-expired")]
+        EmailChangeTokenExpired,
+        #[error("totp-already-enabled")]
+        TotpAlreadyEnabled,
+        #[error("totp-not-enrolled")]
+        TotpNotEnrolled,
+        #[error("totp-not-enabled")]
+        TotpNotEnabled,
+        #[error("invalid-totp-code")]
+        InvalidTotpCode,
+        #[error("invalid-mfa-challenge")]
+        InvalidMfaChallenge,
+        #[error("mfa-challenge-expired")]
+        MfaChallengeExpired,
+        #[error("invalid-recovery-code")]
+        InvalidRecoveryCode,
+        #[error("account-locked")]
+        AccountLocked,
     }
 
+    const MAX_LOCK_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+    const RECOVERY_CODE_COUNT: usize = 10;
+    const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
     lazy_static! {
         static ref EMAIL_REGEX: Regex =
         Regex::new("^[a-zA-Z0-9_+&*-]+(?:\\.[a-zA-Z0-9_+&*-]+)*@(?:[a-zA-Z0-9-]+\\.)+[a-zA-Z]{2,}$").unwrap();
     }
 
     impl User {
-        pub fn new(id: String, email: String, password: String, role: String,
-                   password_reset_requests: Vec<ResetPasswordRequest>) -> Self {
-            Self { id, email, password, role, password_reset_requests }
+        pub fn new(id: String, email: String, password: String, role: String, email_verified: bool,
+                   failed_sign_in_attempts: i32, locked_until: Option<OffsetDateTime>,
+                   password_reset_requests: Vec<ResetPasswordRequest>,
+                   email_verification_requests: Vec<EmailVerificationRequest>,
+                   email_change_request: Option<EmailChangeRequest>,
+                   totp: Option<TotpCredential>,
+                   mfa_challenges: Vec<MfaChallenge>,
+                   recovery_codes: Vec<RecoveryCode>) -> Self {
+            Self {
+                id,
+                email,
+                password,
+                role,
+                email_verified,
+                failed_sign_in_attempts,
+                locked_until,
+                password_reset_requests,
+                email_verification_requests,
+                email_change_request,
+                totp,
+                mfa_challenges,
+                recovery_codes,
+            }
         }
 
         pub fn register(email: String, password: String, username: String) -> Result<(Self, Profile), UserDomainError> {
@@ -82,7 +184,15 @@ pub mod user {
                 email,
                 password,
                 role: "user".to_string(),
-                password_reset_requests: Vec::new()
+                email_verified: false,
+                failed_sign_in_attempts: 0,
+                locked_until: None,
+                password_reset_requests: Vec::new(),
+                email_verification_requests: Vec::new(),
+                email_change_request: None,
+                totp: None,
+                mfa_challenges: Vec::new(),
+                recovery_codes: Vec::new(),
             };
 
             let profile = Profile::register(username, id)?;
@@ -90,8 +200,43 @@ pub mod user {
             Ok((user, profile))
         }
 
-        pub fn login(&self, password: &str) -> Result<(), UserDomainError> {
-            Self::verify_password(&self.password, password)
+        pub fn login(&mut self, password: &str, lockout_policy: &LockoutPolicy) -> Result<(), UserDomainError> {
+            let datetime_now = OffsetDateTime::now_utc();
+
+            if self.is_locked(datetime_now) {
+                return Err(UserDomainError::AccountLocked);
+            }
+
+            match Self::verify_password(&self.password, password) {
+                Ok(()) => {
+                    self.unlock();
+                    Ok(())
+                }
+                Err(UserDomainError::PasswordWrong) => {
+                    self.failed_sign_in_attempts += 1;
+
+                    if self.failed_sign_in_attempts >= lockout_policy.threshold {
+                        let exponent = (self.failed_sign_in_attempts - lockout_policy.threshold).min(16) as u32;
+                        let lock_duration = lockout_policy.lock_duration
+                            .saturating_mul(2u32.pow(exponent))
+                            .min(MAX_LOCK_DURATION);
+
+                        self.locked_until = Some(datetime_now + lock_duration);
+                    }
+
+                    Err(UserDomainError::PasswordWrong)
+                }
+                Err(e) => Err(e)
+            }
+        }
+
+        pub fn is_locked(&self, datetime_now: OffsetDateTime) -> bool {
+            self.locked_until.is_some_and(|locked_until| datetime_now < locked_until)
+        }
+
+        fn unlock(&mut self) {
+            self.failed_sign_in_attempts = 0;
+            self.locked_until = None;
         }
 
         pub fn request_password_reset(&mut self, requester: String) -> Result<DomainEvent, UserDomainError> {
@@ -145,11 +290,335 @@ pub mod user {
             self.password = new_password;
 
             self.password_reset_requests.clear();
+            // Proving ownership of the email also lifts a lockout
+            self.unlock();
 
             // todo domain event
             Ok(())
         }
 
+        pub fn change_password(&mut self, old_password: &str, new_password: &str) -> Result<DomainEvent, UserDomainError> {
+            Self::verify_password(&self.password, old_password)?;
+
+            Self::validate_password(new_password)?;
+            self.password = Self::hash_password(new_password)?;
+
+            self.password_reset_requests.clear();
+
+            Ok(PasswordChanged {
+                user_id: self.id.clone(),
+                email: self.email.clone(),
+                datetime: OffsetDateTime::now_utc(),
+            }.into())
+        }
+
+        pub fn request_email_verification(&mut self) -> Result<DomainEvent, UserDomainError> {
+            if self.email_verified {
+                return Err(UserDomainError::EmailAlreadyVerified);
+            }
+
+            let datetime_now = OffsetDateTime::now_utc();
+            let datetime_one_hour_ago = datetime_now
+                .sub(Duration::from_secs(60 * 60));
+
+            let recent_requests = self.email_verification_requests
+                .iter()
+                .filter(|request| datetime_one_hour_ago.unix_timestamp() < request.datetime.unix_timestamp())
+                .count();
+
+            if recent_requests >= 3 {
+                return Err(UserDomainError::TooManyEmailVerificationsRequested);
+            }
+
+            let token = ChaCha20Rng::from_entropy().next_u64().to_string();
+
+            self.email_verification_requests.push(EmailVerificationRequest {
+                token: token.clone(),
+                datetime: datetime_now,
+            });
+
+            Ok(UserEmailVerificationRequested {
+                token,
+                email: self.email.clone(),
+                datetime: datetime_now,
+            }.into())
+        }
+
+        pub fn verify_email(&mut self, supplied_token: &str) -> Result<(), UserDomainError> {
+            if self.email_verified {
+                return Err(UserDomainError::EmailAlreadyVerified);
+            }
+
+            let found_token = match self.email_verification_requests
+                .iter().find(|request| request.token == supplied_token) {
+                None => return Err(UserDomainError::InvalidEmailVerificationToken),
+                Some(token) => token
+            };
+
+            let one_day_ago = OffsetDateTime::now_utc()
+                .sub(Duration::from_secs(24 * 60 * 60));
+
+            if found_token.datetime.unix_timestamp() < one_day_ago.unix_timestamp() {
+                return Err(UserDomainError::EmailVerificationTokenExpired);
+            }
+
+            self.email_verified = true;
+            self.email_verification_requests.clear();
+
+            Ok(())
+        }
+
+        // Only one email change can be pending, a new request replaces the previous one
+        pub fn request_email_change(&mut self, password: &str, new_email: &str) -> Result<DomainEvent, UserDomainError> {
+            Self::verify_password(&self.password, password)?;
+
+            let new_email = new_email.to_lowercase();
+            Self::validate_email(&new_email)?;
+
+            if new_email == self.email {
+                return Err(UserDomainError::EmailUnchanged);
+            }
+
+            let token = ChaCha20Rng::from_entropy().next_u64().to_string();
+            let datetime_now = OffsetDateTime::now_utc();
+
+            self.email_change_request = Some(EmailChangeRequest {
+                new_email: new_email.clone(),
+                token: token.clone(),
+                datetime: datetime_now,
+            });
+
+            Ok(EmailChangeRequested {
+                user_id: self.id.clone(),
+                old_email: self.email.clone(),
+                new_email,
+                token,
+                datetime: datetime_now,
+            }.into())
+        }
+
+        pub fn confirm_email_change(&mut self, supplied_token: &str) -> Result<(), UserDomainError> {
+            let request = match &self.email_change_request {
+                Some(request) if request.token == supplied_token => request,
+                _ => return Err(UserDomainError::InvalidEmailChangeToken)
+            };
+
+            let one_day_ago = OffsetDateTime::now_utc()
+                .sub(Duration::from_secs(24 * 60 * 60));
+
+            if request.datetime.unix_timestamp() < one_day_ago.unix_timestamp() {
+                return Err(UserDomainError::EmailChangeTokenExpired);
+            }
+
+            self.email = request.new_email.clone();
+            // Following the link proves ownership of the new address
+            self.email_verified = true;
+            self.email_verification_requests.clear();
+            self.email_change_request = None;
+
+            Ok(())
+        }
+
+        // Starts (or restarts) TOTP enrolment, returns the cleartext secret to show to the user once
+        pub fn enroll_totp(&mut self, cipher: &SecretCipher) -> Result<Vec<u8>, UserDomainError> {
+            if self.is_totp_enabled() {
+                return Err(UserDomainError::TotpAlreadyEnabled);
+            }
+
+            let secret = totp::generate_secret();
+
+            self.totp = Some(TotpCredential {
+                encrypted_secret: cipher.encrypt(&secret)
+                    .map_err(UserDomainError::UnexpectedError)?,
+                enabled: false,
+                last_used_step: None,
+            });
+
+            Ok(secret)
+        }
+
+        // Enables the second factor and returns the first batch of recovery codes
+        pub fn confirm_totp(&mut self, cipher: &SecretCipher, code: &str) -> Result<Vec<String>, UserDomainError> {
+            match &self.totp {
+                None => return Err(UserDomainError::TotpNotEnrolled),
+                Some(totp) if totp.enabled => return Err(UserDomainError::TotpAlreadyEnabled),
+                Some(_) => {}
+            }
+
+            self.verify_totp_code(cipher, code)?;
+
+            if let Some(totp) = self.totp.as_mut() {
+                totp.enabled = true;
+            }
+
+            self.generate_recovery_codes()
+        }
+
+        pub fn disable_totp(&mut self, cipher: &SecretCipher, password: &str, code: &str) -> Result<(), UserDomainError> {
+            if !self.is_totp_enabled() {
+                return Err(UserDomainError::TotpNotEnabled);
+            }
+
+            Self::verify_password(&self.password, password)?;
+            self.verify_totp_code(cipher, code)?;
+
+            self.totp = None;
+            self.mfa_challenges.clear();
+            self.recovery_codes.clear();
+
+            Ok(())
+        }
+
+        pub fn is_totp_enabled(&self) -> bool {
+            self.totp.as_ref().is_some_and(|totp| totp.enabled)
+        }
+
+        // Issued after a correct password when a second factor is required
+        pub fn create_mfa_challenge(&mut self) -> String {
+            let datetime_now = OffsetDateTime::now_utc();
+
+            self.mfa_challenges.retain(|challenge| !challenge.is_expired(datetime_now));
+
+            let id = Uuid::new_v4().to_string();
+
+            self.mfa_challenges.push(MfaChallenge {
+                id: id.clone(),
+                datetime: datetime_now,
+                failed_attempts: 0,
+            });
+
+            id
+        }
+
+        pub fn complete_mfa_challenge(&mut self, challenge_id: &str, cipher: &SecretCipher, code: &str) -> Result<(), UserDomainError> {
+            self.attempt_mfa_challenge(challenge_id, |user| user.verify_totp_code(cipher, code))
+        }
+
+        pub fn complete_mfa_challenge_with_recovery_code(&mut self, challenge_id: &str, recovery_code: &str) -> Result<DomainEvent, UserDomainError> {
+            self.attempt_mfa_challenge(challenge_id, |user| user.use_recovery_code(recovery_code))
+        }
+
+        // Replaces all existing recovery codes, requires the password since the old codes stop working
+        pub fn regenerate_recovery_codes(&mut self, password: &str) -> Result<Vec<String>, UserDomainError> {
+            if !self.is_totp_enabled() {
+                return Err(UserDomainError::TotpNotEnabled);
+            }
+
+            Self::verify_password(&self.password, password)?;
+
+            self.generate_recovery_codes()
+        }
+
+        fn generate_recovery_codes(&mut self) -> Result<Vec<String>, UserDomainError> {
+            let mut rng = ChaCha20Rng::from_entropy();
+
+            let codes = (0..RECOVERY_CODE_COUNT)
+                .map(|_| {
+                    let code: String = (0..10)
+                        .map(|_| RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+                        .collect();
+
+                    format!("{}-{}", &code[..5], &code[5..])
+                })
+                .collect::<Vec<_>>();
+
+            self.recovery_codes = codes
+                .iter()
+                .map(|code| Self::hash_password(code).map(|hash| RecoveryCode { hash }))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(codes)
+        }
+
+        fn use_recovery_code(&mut self, recovery_code: &str) -> Result<DomainEvent, UserDomainError> {
+            let recovery_code = recovery_code.trim().to_lowercase();
+
+            let mut found_index = None;
+
+            for (index, stored_code) in self.recovery_codes.iter().enumerate() {
+                match Self::verify_password(&stored_code.hash, &recovery_code) {
+                    Ok(()) => {
+                        found_index = Some(index);
+                        break;
+                    }
+                    Err(UserDomainError::PasswordWrong) => continue,
+                    Err(e) => return Err(e)
+                }
+            }
+
+            let index = found_index.ok_or(UserDomainError::InvalidRecoveryCode)?;
+            self.recovery_codes.remove(index);
+
+            Ok(RecoveryCodeUsed {
+                user_id: self.id.clone(),
+                email: self.email.clone(),
+                remaining_codes: self.recovery_codes.len(),
+                datetime: OffsetDateTime::now_utc(),
+            }.into())
+        }
+
+        // Failed attempts count towards the challenge limit, a successful one consumes the challenge
+        fn attempt_mfa_challenge<T>(&mut self, challenge_id: &str,
+                                    verify: impl FnOnce(&mut Self) -> Result<T, UserDomainError>) -> Result<T, UserDomainError> {
+            let datetime_now = OffsetDateTime::now_utc();
+
+            let challenge_index = self.mfa_challenges
+                .iter()
+                .position(|challenge| challenge.id == challenge_id)
+                .ok_or(UserDomainError::InvalidMfaChallenge)?;
+
+            if self.mfa_challenges[challenge_index].is_expired(datetime_now) {
+                self.mfa_challenges.remove(challenge_index);
+                return Err(UserDomainError::MfaChallengeExpired);
+            }
+
+            let result = match verify(self) {
+                Ok(result) => result,
+                Err(e) => {
+                    let challenge = &mut self.mfa_challenges[challenge_index];
+                    challenge.failed_attempts += 1;
+
+                    if challenge.failed_attempts >= MfaChallenge::MAX_FAILED_ATTEMPTS {
+                        self.mfa_challenges.remove(challenge_index);
+                    }
+
+                    return Err(e);
+                }
+            };
+
+            self.mfa_challenges.remove(challenge_index);
+
+            Ok(result)
+        }
+
+        // Codes can only be used once, a code from an already used time step is rejected
+        fn verify_totp_code(&mut self, cipher: &SecretCipher, code: &str) -> Result<(), UserDomainError> {
+            let totp = self.totp.as_mut()
+                .ok_or(UserDomainError::TotpNotEnrolled)?;
+
+            let secret = cipher.decrypt(&totp.encrypted_secret)
+                .map_err(UserDomainError::UnexpectedError)?;
+
+            let step = totp::verify(&secret, code, OffsetDateTime::now_utc())
+                .ok_or(UserDomainError::InvalidTotpCode)?;
+
+            if totp.last_used_step.is_some_and(|last_used_step| step <= last_used_step) {
+                return Err(UserDomainError::InvalidTotpCode);
+            }
+
+            totp.last_used_step = Some(step);
+
+            Ok(())
+        }
+
+        pub fn ensure_email_verified(&self) -> Result<(), UserDomainError> {
+            if !self.email_verified {
+                return Err(UserDomainError::EmailNotVerified);
+            }
+
+            Ok(())
+        }
+
         // Valid email test (OWASP Regex + maximum length of 60 graphemes)
         // todo unit tests
         pub fn validate_email(email: &str) -> Result<(), UserDomainError> {
@@ -203,6 +672,18 @@ pub mod user {
             &self.role
         }
 
+        pub fn is_email_verified(&self) -> bool {
+            self.email_verified
+        }
+
+        pub fn failed_sign_in_attempts(&self) -> i32 {
+            self.failed_sign_in_attempts
+        }
+
+        pub fn locked_until(&self) -> Option<OffsetDateTime> {
+            self.locked_until
+        }
+
         // todo unit tests
         fn hash_password(cleartext_password: &str) -> Result<String, UserDomainError> {
             let password_salt = SaltString::generate(&mut OsRng);
@@ -228,6 +709,26 @@ pub mod user {
         pub fn password_reset_requests(&self) -> &Vec<ResetPasswordRequest> {
             &self.password_reset_requests
         }
+
+        pub fn email_verification_requests(&self) -> &Vec<EmailVerificationRequest> {
+            &self.email_verification_requests
+        }
+
+        pub fn email_change_request(&self) -> Option<&EmailChangeRequest> {
+            self.email_change_request.as_ref()
+        }
+
+        pub fn totp(&self) -> Option<&TotpCredential> {
+            self.totp.as_ref()
+        }
+
+        pub fn mfa_challenges(&self) -> &Vec<MfaChallenge> {
+            &self.mfa_challenges
+        }
+
+        pub fn recovery_codes(&self) -> &Vec<RecoveryCode> {
+            &self.recovery_codes
+        }
     }
 
     impl ResetPasswordRequest {
@@ -243,4 +744,133 @@ pub mod user {
             self.datetime
         }
     }
+
+    impl EmailVerificationRequest {
+        pub fn new(token: String, datetime: OffsetDateTime) -> Self {
+            Self { token, datetime }
+        }
+
+        pub fn token(&self) -> &str {
+            &self.token
+        }
+
+        pub fn datetime(&self) -> OffsetDateTime {
+            self.datetime
+        }
+    }
+
+    impl EmailChangeRequest {
+        pub fn new(new_email: String, token: String, datetime: OffsetDateTime) -> Self {
+            Self { new_email, token, datetime }
+        }
+
+        pub fn new_email(&self) -> &str {
+            &self.new_email
+        }
+
+        pub fn token(&self) -> &str {
+            &self.token
+        }
+
+        pub fn datetime(&self) -> OffsetDateTime {
+            self.datetime
+        }
+    }
+
+    impl TotpCredential {
+        pub fn new(encrypted_secret: Vec<u8>, enabled: bool, last_used_step: Option<i64>) -> Self {
+            Self { encrypted_secret, enabled, last_used_step }
+        }
+
+        pub fn encrypted_secret(&self) -> &[u8] {
+            &self.encrypted_secret
+        }
+
+        pub fn enabled(&self) -> bool {
+            self.enabled
+        }
+
+        pub fn last_used_step(&self) -> Option<i64> {
+            self.last_used_step
+        }
+    }
+
+    impl MfaChallenge {
+        const MAX_FAILED_ATTEMPTS: i32 = 5;
+        const VALIDITY: Duration = Duration::from_secs(5 * 60);
+
+        pub fn new(id: String, datetime: OffsetDateTime, failed_attempts: i32) -> Self {
+            Self { id, datetime, failed_attempts }
+        }
+
+        pub fn id(&self) -> &str {
+            &self.id
+        }
+
+        pub fn datetime(&self) -> OffsetDateTime {
+            self.datetime
+        }
+
+        pub fn failed_attempts(&self) -> i32 {
+            self.failed_attempts
+        }
+
+        fn is_expired(&self, datetime_now: OffsetDateTime) -> bool {
+            self.datetime.unix_timestamp() < datetime_now.sub(Self::VALIDITY).unix_timestamp()
+        }
+    }
+
+    impl RecoveryCode {
+        pub fn new(hash: String) -> Self {
+            Self { hash }
+        }
+
+        pub fn hash(&self) -> &str {
+            &self.hash
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use time::OffsetDateTime;
+
+        use crate::infrastructure::secret_cipher::SecretCipher;
+        use crate::infrastructure::totp;
+
+        use super::*;
+
+        const PASSWORD: &str = "correct horse battery";
+
+        fn user_with_totp(cipher: &SecretCipher) -> (User, Vec<u8>) {
+            let (mut user, _) = User::register("alice@example.com".to_string(), PASSWORD.to_string(), "alice".to_string()).unwrap();
+            let secret = user.enroll_totp(cipher).unwrap();
+
+            (user, secret)
+        }
+
+        #[test]
+        fn rejects_a_totp_code_that_was_already_used() {
+            let cipher = SecretCipher::new("test");
+            let (mut user, secret) = user_with_totp(&cipher);
+
+            let code = totp::code_at(&secret, OffsetDateTime::now_utc());
+            user.confirm_totp(&cipher, &code).unwrap();
+
+            assert!(matches!(user.disable_totp(&cipher, PASSWORD, &code), Err(UserDomainError::InvalidTotpCode)));
+            assert!(user.is_totp_enabled());
+        }
+
+        #[test]
+        fn rejects_a_totp_code_from_an_earlier_step() {
+            let cipher = SecretCipher::new("test");
+            let (mut user, secret) = user_with_totp(&cipher);
+
+            let now = OffsetDateTime::now_utc();
+            user.confirm_totp(&cipher, &totp::code_at(&secret, now)).unwrap();
+
+            let previous_code = totp::code_at(&secret, now - time::Duration::seconds(30));
+
+            assert!(matches!(user.disable_totp(&cipher, PASSWORD, &previous_code), Err(UserDomainError::InvalidTotpCode)));
+        }
+    }
 }
@@ -8,13 +8,14 @@ mod auth_connector {
     use std::str::FromStr;
 
     use async_trait::async_trait;
+    use time::OffsetDateTime;
     use tonic::codegen::InterceptedService;
     use tonic::transport::{Channel, Endpoint};
     use tower::ServiceBuilder;
 
     use auth_client::AuthClient;
 
-    use crate::application::connectors::auth_connector::{AuthConnector, AuthConnectorError};
+    use crate::application::connectors::auth_connector::{AuthConnector, AuthConnectorError, SessionInfo, SessionMetadata};
     use crate::infrastructure::connectors::auth_connector::CorrelationIdInterceptor;
 
     tonic::include_proto!("auth");
@@ -44,10 +45,12 @@ mod auth_connector {
 
     #[async_trait]
     impl AuthConnector for GrpcAuthConnector {
-        async fn create_session(&self, user_id: String, profile_id: String) -> Result<String, AuthConnectorError> {
+        async fn create_session(&self, user_id: String, profile_id: String, metadata: SessionMetadata) -> Result<String, AuthConnectorError> {
             let request = tonic::Request::new(CreateSessionRequest {
                 user_id,
                 profile_id,
+                ip_address: metadata.ip_address,
+                device: metadata.device,
             });
 
             self.client
@@ -57,6 +60,79 @@ mod auth_connector {
                 .map(|response| response.into_inner().session_token)
                 .map_err(|status| AuthConnectorError::UnexpectedError(anyhow::Error::msg(status)))
         }
+
+        async fn revoke_session(&self, session_token: String) -> Result<(), AuthConnectorError> {
+            let request = tonic::Request::new(RevokeSessionRequest {
+                session_token,
+            });
+
+            self.client
+                .clone()
+                .revoke_session(request)
+                .await
+                .map(|_| ())
+                .map_err(|status| AuthConnectorError::UnexpectedError(anyhow::Error::msg(status)))
+        }
+
+        async fn revoke_session_by_id(&self, user_id: String, session_id: String) -> Result<(), AuthConnectorError> {
+            let request = tonic::Request::new(RevokeSessionByIdRequest {
+                user_id,
+                session_id,
+            });
+
+            let revoked = self.client
+                .clone()
+                .revoke_session_by_id(request)
+                .await
+                .map(|response| response.into_inner().revoked)
+                .map_err(|status| AuthConnectorError::UnexpectedError(anyhow::Error::msg(status)))?;
+
+            match revoked {
+                true => Ok(()),
+                false => Err(AuthConnectorError::SessionNotFound)
+            }
+        }
+
+        async fn revoke_all_sessions_for_user(&self, user_id: String) -> Result<(), AuthConnectorError> {
+            let request = tonic::Request::new(RevokeAllSessionsForUserRequest {
+                user_id,
+            });
+
+            self.client
+                .clone()
+                .revoke_all_sessions_for_user(request)
+                .await
+                .map(|_| ())
+                .map_err(|status| AuthConnectorError::UnexpectedError(anyhow::Error::msg(status)))
+        }
+
+        async fn list_sessions(&self, user_id: String, current_session_token: Option<String>) -> Result<Vec<SessionInfo>, AuthConnectorError> {
+            let request = tonic::Request::new(ListSessionsRequest {
+                user_id,
+                current_session_token: current_session_token.unwrap_or_default(),
+            });
+
+            let sessions = self.client
+                .clone()
+                .list_sessions(request)
+                .await
+                .map(|response| response.into_inner().sessions)
+                .map_err(|status| AuthConnectorError::UnexpectedError(anyhow::Error::msg(status)))?;
+
+            sessions
+                .into_iter()
+                .map(|session| Ok(SessionInfo {
+                    id: session.session_id,
+                    device: session.device,
+                    ip_address: session.ip_address,
+                    created_at: OffsetDateTime::from_unix_timestamp(session.created_at)
+                        .map_err(|e| AuthConnectorError::UnexpectedError(e.into()))?,
+                    last_seen_at: OffsetDateTime::from_unix_timestamp(session.last_seen_at)
+                        .map_err(|e| AuthConnectorError::UnexpectedError(e.into()))?,
+                    current: session.current,
+                }))
+                .collect()
+        }
     }
 }
 
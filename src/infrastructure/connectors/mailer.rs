@@ -0,0 +1,124 @@
+pub use file_mailer::FileMailer;
+pub use in_memory_mailer::InMemoryMailer;
+pub use smtp_mailer::SmtpMailer;
+
+mod smtp_mailer {
+    use async_trait::async_trait;
+    use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+    use lettre::message::header::ContentType;
+    use lettre::message::Mailbox;
+    use lettre::transport::smtp::authentication::Credentials;
+
+    use crate::application::connectors::mailer::{Mail, Mailer, MailerError};
+
+    #[derive(Clone)]
+    pub struct SmtpMailer {
+        transport: AsyncSmtpTransport<Tokio1Executor>,
+        from: Mailbox,
+    }
+
+    impl SmtpMailer {
+        pub fn new(host: &str, port: u16, username: String, password: String, from: &str) -> Result<Self, anyhow::Error> {
+            let transport = AsyncSmtpTransport::<Tokio1Executor>::relay(host)?
+                .port(port)
+                .credentials(Credentials::new(username, password))
+                .build();
+
+            Ok(Self {
+                transport,
+                from: from.parse()?,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Mailer for SmtpMailer {
+        async fn send(&self, mail: Mail) -> Result<(), MailerError> {
+            let to: Mailbox = mail.to.parse()
+                .map_err(|e: lettre::address::AddressError| MailerError::UnexpectedError(e.into()))?;
+
+            let message = Message::builder()
+                .from(self.from.clone())
+                .to(to)
+                .subject(mail.subject)
+                .header(ContentType::TEXT_PLAIN)
+                .body(mail.body)
+                .map_err(|e| MailerError::UnexpectedError(e.into()))?;
+
+            self.transport
+                .send(message)
+                .await
+                .map(|_| ())
+                .map_err(|e| MailerError::UnexpectedError(e.into()))
+        }
+    }
+}
+
+mod file_mailer {
+    use std::path::PathBuf;
+
+    use async_trait::async_trait;
+    use time::OffsetDateTime;
+    use uuid::Uuid;
+
+    use crate::application::connectors::mailer::{Mail, Mailer, MailerError};
+
+    // Writes every mail to a file in the given directory, meant for local runs
+    #[derive(Clone)]
+    pub struct FileMailer {
+        directory: PathBuf,
+    }
+
+    impl FileMailer {
+        pub fn new(directory: PathBuf) -> Self {
+            Self { directory }
+        }
+    }
+
+    #[async_trait]
+    impl Mailer for FileMailer {
+        async fn send(&self, mail: Mail) -> Result<(), MailerError> {
+            tokio::fs::create_dir_all(&self.directory)
+                .await
+                .map_err(|e| MailerError::UnexpectedError(e.into()))?;
+
+            let file_name = format!("{}-{}.txt", OffsetDateTime::now_utc().unix_timestamp(), Uuid::new_v4());
+            let contents = format!("To: {}\nSubject: {}\n\n{}\n", mail.to, mail.subject, mail.body);
+
+            tokio::fs::write(self.directory.join(file_name), contents)
+                .await
+                .map_err(|e| MailerError::UnexpectedError(e.into()))
+        }
+    }
+}
+
+mod in_memory_mailer {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+
+    use crate::application::connectors::mailer::{Mail, Mailer, MailerError};
+
+    // Keeps every mail in memory so they can be inspected, clones share the same outbox
+    #[derive(Clone, Default)]
+    pub struct InMemoryMailer(Arc<Mutex<Vec<Mail>>>);
+
+    impl InMemoryMailer {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        #[cfg(test)]
+        pub fn sent_mails(&self) -> Vec<Mail> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl Mailer for InMemoryMailer {
+        async fn send(&self, mail: Mail) -> Result<(), MailerError> {
+            self.0.lock().unwrap().push(mail);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,173 @@
+pub use local_media_storage::LocalMediaStorage;
+pub use s3_media_storage::S3MediaStorage;
+
+fn public_url(base_url: &str, key: &str) -> String {
+    format!("{}/{}", base_url.trim_end_matches('/'), key)
+}
+
+fn key_from_url(base_url: &str, url: &str) -> Option<String> {
+    url.strip_prefix(base_url.trim_end_matches('/'))
+        .and_then(|path| path.strip_prefix('/'))
+        .filter(|key| !key.is_empty() && !key.split('/').any(|segment| segment == ".."))
+        .map(|key| key.to_string())
+}
+
+mod local_media_storage {
+    use std::path::PathBuf;
+
+    use async_trait::async_trait;
+
+    use crate::application::connectors::media_storage::{MediaStorage, MediaStorageError};
+
+    // Writes objects to a directory, serving it under the public url is left to the reverse proxy
+    #[derive(Clone)]
+    pub struct LocalMediaStorage {
+        directory: PathBuf,
+        public_url: String,
+    }
+
+    impl LocalMediaStorage {
+        pub fn new(directory: PathBuf, public_url: String) -> Self {
+            Self {
+                directory,
+                public_url,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl MediaStorage for LocalMediaStorage {
+        async fn put(&self, key: &str, _content_type: &str, data: Vec<u8>) -> Result<String, MediaStorageError> {
+            let path = self.directory.join(key);
+
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent)
+                    .await
+                    .map_err(|e| MediaStorageError::UnexpectedError(e.into()))?;
+            }
+
+            tokio::fs::write(&path, data)
+                .await
+                .map_err(|e| MediaStorageError::UnexpectedError(e.into()))?;
+
+            Ok(super::public_url(&self.public_url, key))
+        }
+
+        async fn delete_by_prefix(&self, prefix: &str) -> Result<(), MediaStorageError> {
+            let (directory, file_prefix) = match prefix.rsplit_once('/') {
+                Some((directory, file_prefix)) => (self.directory.join(directory), file_prefix),
+                None => (self.directory.clone(), prefix)
+            };
+
+            let mut entries = match tokio::fs::read_dir(&directory).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+                Err(e) => return Err(MediaStorageError::UnexpectedError(e.into()))
+            };
+
+            while let Some(entry) = entries.next_entry()
+                .await
+                .map_err(|e| MediaStorageError::UnexpectedError(e.into()))?
+            {
+                if entry.file_name().to_string_lossy().starts_with(file_prefix) {
+                    tokio::fs::remove_file(entry.path())
+                        .await
+                        .map_err(|e| MediaStorageError::UnexpectedError(e.into()))?;
+                }
+            }
+
+            Ok(())
+        }
+
+        fn key_from_url(&self, url: &str) -> Option<String> {
+            super::key_from_url(&self.public_url, url)
+        }
+    }
+}
+
+mod s3_media_storage {
+    use async_trait::async_trait;
+    use aws_sdk_s3::Client;
+    use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+    use aws_sdk_s3::primitives::ByteStream;
+
+    use crate::application::connectors::media_storage::{MediaStorage, MediaStorageError};
+
+    // Works with any S3 compatible service, set an endpoint to use something like MinIO
+    #[derive(Clone)]
+    pub struct S3MediaStorage {
+        client: Client,
+        bucket: String,
+        public_url: String,
+    }
+
+    impl S3MediaStorage {
+        pub fn new(bucket: String, region: String, endpoint: Option<String>,
+                   access_key_id: String, secret_access_key: String,
+                   public_url: String) -> Self
+        {
+            let credentials = Credentials::new(access_key_id, secret_access_key, None, None, "environment");
+
+            let mut config = aws_sdk_s3::Config::builder()
+                .behavior_version(BehaviorVersion::latest())
+                .region(Region::new(region))
+                .credentials_provider(credentials);
+
+            // Self-hosted services usually don't support virtual-hosted buckets
+            if let Some(endpoint) = endpoint {
+                config = config
+                    .endpoint_url(endpoint)
+                    .force_path_style(true);
+            }
+
+            Self {
+                client: Client::from_conf(config.build()),
+                bucket,
+                public_url,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl MediaStorage for S3MediaStorage {
+        async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> Result<String, MediaStorageError> {
+            self.client
+                .put_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .content_type(content_type)
+                .body(ByteStream::from(data))
+                .send()
+                .await
+                .map_err(|e| MediaStorageError::UnexpectedError(e.into()))?;
+
+            Ok(super::public_url(&self.public_url, key))
+        }
+
+        async fn delete_by_prefix(&self, prefix: &str) -> Result<(), MediaStorageError> {
+            let objects = self.client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(prefix)
+                .send()
+                .await
+                .map_err(|e| MediaStorageError::UnexpectedError(e.into()))?;
+
+            for key in objects.contents().iter().filter_map(|object| object.key()) {
+                self.client
+                    .delete_object()
+                    .bucket(&self.bucket)
+                    .key(key)
+                    .send()
+                    .await
+                    .map_err(|e| MediaStorageError::UnexpectedError(e.into()))?;
+            }
+
+            Ok(())
+        }
+
+        fn key_from_url(&self, url: &str) -> Option<String> {
+            super::key_from_url(&self.public_url, url)
+        }
+    }
+}
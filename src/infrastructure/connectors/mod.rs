@@ -1,3 +1,7 @@
 pub use auth_connector::GrpcAuthConnector;
+pub use mailer::{FileMailer, InMemoryMailer, SmtpMailer};
+pub use media_storage::{LocalMediaStorage, S3MediaStorage};
 
 mod auth_connector;
+mod mailer;
+mod media_storage;
@@ -0,0 +1,40 @@
+use time::PrimitiveDateTime;
+use tokio_postgres::Row;
+
+use crate::application::errors::RepositoryError;
+use crate::application::pagination::DatedCursor;
+use crate::domain::Profile;
+use crate::infrastructure::database::entities::ProfileEntity;
+
+// A profile in a dated list, the row holds the profile columns and when it was added as listed_at
+pub struct DatedProfileEntity {
+    profile: ProfileEntity,
+    listed_at: PrimitiveDateTime,
+}
+
+impl TryFrom<Row> for DatedProfileEntity {
+    type Error = RepositoryError;
+
+    fn try_from(value: Row) -> Result<Self, Self::Error> {
+        let listed_at = value.try_get("listed_at")?;
+        let profile = ProfileEntity::try_from(value)?;
+
+        Ok(Self {
+            profile,
+            listed_at,
+        })
+    }
+}
+
+impl From<DatedProfileEntity> for (Profile, DatedCursor) {
+    fn from(value: DatedProfileEntity) -> Self {
+        let profile: Profile = value.profile.into();
+
+        let cursor = DatedCursor {
+            created_at: value.listed_at.assume_utc(),
+            profile_id: profile.get_id(),
+        };
+
+        (profile, cursor)
+    }
+}
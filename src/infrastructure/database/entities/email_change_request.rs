@@ -0,0 +1,37 @@
+use time::{OffsetDateTime, PrimitiveDateTime};
+use tokio_postgres::Row;
+
+use crate::application::errors::RepositoryError;
+use crate::domain::user::user::EmailChangeRequest;
+
+pub struct EmailChangeRequestEntity {
+    token: String,
+    user_id: String,
+    new_email: String,
+    datetime: OffsetDateTime,
+}
+
+impl TryFrom<Row> for EmailChangeRequestEntity {
+    type Error = RepositoryError;
+
+    fn try_from(value: Row) -> Result<Self, Self::Error> {
+        let token = value.try_get("token")?;
+        let user_id = value.try_get("user_id")?;
+        let new_email = value.try_get("new_email")?;
+        let datetime = value.try_get::<_, PrimitiveDateTime>("datetime")?
+            .assume_utc();
+
+        Ok(Self {
+            token,
+            user_id,
+            new_email,
+            datetime,
+        })
+    }
+}
+
+impl From<EmailChangeRequestEntity> for EmailChangeRequest {
+    fn from(value: EmailChangeRequestEntity) -> Self {
+        Self::new(value.new_email, value.token, value.datetime)
+    }
+}
@@ -0,0 +1,34 @@
+use time::{OffsetDateTime, PrimitiveDateTime};
+use tokio_postgres::Row;
+
+use crate::application::errors::RepositoryError;
+use crate::domain::user::user::EmailVerificationRequest;
+
+pub struct EmailVerificationRequestEntity {
+    token: String,
+    user_id: String,
+    datetime: OffsetDateTime,
+}
+
+impl TryFrom<Row> for EmailVerificationRequestEntity {
+    type Error = RepositoryError;
+
+    fn try_from(value: Row) -> Result<Self, Self::Error> {
+        let token = value.try_get("token")?;
+        let user_id = value.try_get("user_id")?;
+        let datetime = value.try_get::<_, PrimitiveDateTime>("datetime")?
+            .assume_utc();
+
+        Ok(Self {
+            token,
+            user_id,
+            datetime,
+        })
+    }
+}
+
+impl From<EmailVerificationRequestEntity> for EmailVerificationRequest {
+    fn from(value: EmailVerificationRequestEntity) -> Self {
+        Self::new(value.token, value.datetime)
+    }
+}
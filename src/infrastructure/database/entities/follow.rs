@@ -0,0 +1,33 @@
+use time::PrimitiveDateTime;
+use tokio_postgres::Row;
+
+use crate::application::errors::RepositoryError;
+use crate::domain::Follow;
+
+pub struct FollowEntity {
+    follower_id: String,
+    followee_id: String,
+    created_at: PrimitiveDateTime,
+}
+
+impl TryFrom<Row> for FollowEntity {
+    type Error = RepositoryError;
+
+    fn try_from(value: Row) -> Result<Self, Self::Error> {
+        let follower_id = value.try_get("follower_id")?;
+        let followee_id = value.try_get("followee_id")?;
+        let created_at = value.try_get("created_at")?;
+
+        Ok(Self {
+            follower_id,
+            followee_id,
+            created_at,
+        })
+    }
+}
+
+impl From<FollowEntity> for Follow {
+    fn from(value: FollowEntity) -> Self {
+        Follow::new(value.follower_id, value.followee_id, value.created_at.assume_utc())
+    }
+}
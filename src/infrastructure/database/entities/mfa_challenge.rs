@@ -0,0 +1,37 @@
+use time::{OffsetDateTime, PrimitiveDateTime};
+use tokio_postgres::Row;
+
+use crate::application::errors::RepositoryError;
+use crate::domain::user::user::MfaChallenge;
+
+pub struct MfaChallengeEntity {
+    id: String,
+    user_id: String,
+    datetime: OffsetDateTime,
+    failed_attempts: i32,
+}
+
+impl TryFrom<Row> for MfaChallengeEntity {
+    type Error = RepositoryError;
+
+    fn try_from(value: Row) -> Result<Self, Self::Error> {
+        let id = value.try_get("id")?;
+        let user_id = value.try_get("user_id")?;
+        let datetime = value.try_get::<_, PrimitiveDateTime>("datetime")?
+            .assume_utc();
+        let failed_attempts = value.try_get("failed_attempts")?;
+
+        Ok(Self {
+            id,
+            user_id,
+            datetime,
+            failed_attempts,
+        })
+    }
+}
+
+impl From<MfaChallengeEntity> for MfaChallenge {
+    fn from(value: MfaChallengeEntity) -> Self {
+        Self::new(value.id, value.datetime, value.failed_attempts)
+    }
+}
@@ -1,8 +1,19 @@
+pub use dated_profile::DatedProfileEntity;
+pub use email_change_request::EmailChangeRequestEntity;
+pub use email_verification_request::EmailVerificationRequestEntity;
+pub use follow::FollowEntity;
+pub use mfa_challenge::MfaChallengeEntity;
 pub use password_reset_request::ResetPasswordRequestEntity;
 pub use profile::ProfileEntity;
+pub use recovery_code::RecoveryCodeEntity;
 pub use user::UserEntity;
 
 mod profile;
 mod user;
 mod password_reset_request;
-
+mod email_verification_request;
+mod email_change_request;
+mod mfa_challenge;
+mod recovery_code;
+mod follow;
+mod dated_profile;
@@ -1,10 +1,11 @@
 pub use profile_entity::ProfileEntity;
 
 mod profile_entity {
+    use time::{OffsetDateTime, PrimitiveDateTime};
     use tokio_postgres::Row;
 
     use crate::application::errors::RepositoryError;
-    use crate::domain::Profile;
+    use crate::domain::{Profile, Visibility};
 
     pub struct ProfileEntity {
         id: String,
@@ -14,6 +15,12 @@ mod profile_entity {
         banner: Option<String>,
         profile_picture: Option<String>,
         user_id: String,
+        username_changed_at: Option<OffsetDateTime>,
+        follower_count: i64,
+        following_count: i64,
+        visibility: Visibility,
+        display_name_visibility: Visibility,
+        bio_visibility: Visibility,
     }
 
     impl TryFrom<Row> for ProfileEntity {
@@ -30,6 +37,15 @@ mod profile_entity {
             let banner: Option<String> = value.try_get("banner").ok();
             let profile_picture: Option<String> = value.try_get("profile_picture").ok();
             let user_id = value.try_get("user_id")?;
+            let username_changed_at = value.try_get::<_, Option<PrimitiveDateTime>>("username_changed_at")
+                .ok()
+                .flatten()
+                .map(|datetime| datetime.assume_utc());
+            let follower_count = value.try_get("follower_count").unwrap_or(0);
+            let following_count = value.try_get("following_count").unwrap_or(0);
+            let visibility = Self::try_get_visibility(&value, "visibility")?;
+            let display_name_visibility = Self::try_get_visibility(&value, "display_name_visibility")?;
+            let bio_visibility = Self::try_get_visibility(&value, "bio_visibility")?;
 
             Ok(Self {
                 id,
@@ -39,10 +55,25 @@ mod profile_entity {
                 banner,
                 profile_picture,
                 user_id,
+                username_changed_at,
+                follower_count,
+                following_count,
+                visibility,
+                display_name_visibility,
+                bio_visibility,
             })
         }
     }
 
+    impl ProfileEntity {
+        fn try_get_visibility(row: &Row, column: &str) -> Result<Visibility, RepositoryError> {
+            let value: String = row.try_get(column)?;
+
+            value.parse()
+                .map_err(|_| RepositoryError::UnexpectedError(anyhow::Error::msg(format!("Unknown {column}: {value}"))))
+        }
+    }
+
     impl From<ProfileEntity> for Profile {
         fn from(entity: ProfileEntity) -> Self {
             Profile {
@@ -53,6 +84,12 @@ mod profile_entity {
                 banner: entity.banner,
                 profile_picture: entity.profile_picture,
                 user_id: entity.user_id,
+                username_changed_at: entity.username_changed_at,
+                follower_count: entity.follower_count,
+                following_count: entity.following_count,
+                visibility: entity.visibility,
+                display_name_visibility: entity.display_name_visibility,
+                bio_visibility: entity.bio_visibility,
             }
         }
     }
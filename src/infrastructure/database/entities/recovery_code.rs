@@ -0,0 +1,29 @@
+use tokio_postgres::Row;
+
+use crate::application::errors::RepositoryError;
+use crate::domain::user::user::RecoveryCode;
+
+pub struct RecoveryCodeEntity {
+    user_id: String,
+    code_hash: String,
+}
+
+impl TryFrom<Row> for RecoveryCodeEntity {
+    type Error = RepositoryError;
+
+    fn try_from(value: Row) -> Result<Self, Self::Error> {
+        let user_id = value.try_get("user_id")?;
+        let code_hash = value.try_get("code_hash")?;
+
+        Ok(Self {
+            user_id,
+            code_hash,
+        })
+    }
+}
+
+impl From<RecoveryCodeEntity> for RecoveryCode {
+    fn from(value: RecoveryCodeEntity) -> Self {
+        Self::new(value.code_hash)
+    }
+}
@@ -1,17 +1,24 @@
 pub use user_entity::UserEntity;
 
 mod user_entity {
+    use time::{OffsetDateTime, PrimitiveDateTime};
     use tokio_postgres::Row;
 
     use crate::application::errors::RepositoryError;
     use crate::domain::User;
-    use crate::domain::user::user::ResetPasswordRequest;
+    use crate::domain::user::user::{EmailChangeRequest, EmailVerificationRequest, MfaChallenge, RecoveryCode, ResetPasswordRequest, TotpCredential};
 
     pub struct UserEntity {
         pub id: String,
         pub email: String,
         pub password: String,
         pub role: String,
+        pub email_verified: bool,
+        pub failed_sign_in_attempts: i32,
+        pub locked_until: Option<OffsetDateTime>,
+        pub totp_secret: Option<Vec<u8>>,
+        pub totp_enabled: bool,
+        pub totp_last_used_step: Option<i64>,
     }
 
     impl TryFrom<Row> for UserEntity {
@@ -25,24 +32,52 @@ mod user_entity {
             let email = value.try_get("email")?;
             let password = value.try_get("password")?;
             let role = value.try_get("role")?;
+            let email_verified = value.try_get("email_verified")?;
+            let failed_sign_in_attempts = value.try_get("failed_sign_in_attempts")?;
+            let locked_until = value.try_get::<_, Option<PrimitiveDateTime>>("locked_until")?
+                .map(|datetime| datetime.assume_utc());
+            let totp_secret = value.try_get("totp_secret")?;
+            let totp_enabled = value.try_get("totp_enabled")?;
+            let totp_last_used_step = value.try_get("totp_last_used_step")?;
 
             Ok(Self {
                 id,
                 email,
                 password,
                 role,
+                email_verified,
+                failed_sign_in_attempts,
+                locked_until,
+                totp_secret,
+                totp_enabled,
+                totp_last_used_step,
             })
         }
     }
 
     impl UserEntity {
-        pub fn into_user(self, reset_password_requests: Vec<ResetPasswordRequest>) -> User {
+        pub fn into_user(self, reset_password_requests: Vec<ResetPasswordRequest>,
+                         email_verification_requests: Vec<EmailVerificationRequest>,
+                         email_change_request: Option<EmailChangeRequest>,
+                         mfa_challenges: Vec<MfaChallenge>,
+                         recovery_codes: Vec<RecoveryCode>) -> User {
+            let totp = self.totp_secret
+                .map(|secret| TotpCredential::new(secret, self.totp_enabled, self.totp_last_used_step));
+
             User::new(
                 self.id,
                 self.email,
                 self.password,
                 self.role,
+                self.email_verified,
+                self.failed_sign_in_attempts,
+                self.locked_until,
                 reset_password_requests,
+                email_verification_requests,
+                email_change_request,
+                totp,
+                mfa_challenges,
+                recovery_codes,
             )
         }
     }
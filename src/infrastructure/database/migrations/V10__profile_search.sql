@@ -0,0 +1,4 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX profile_username_trgm_index ON profile USING gin (username gin_trgm_ops);
+CREATE INDEX profile_display_name_trgm_index ON profile USING gin (display_name gin_trgm_ops);
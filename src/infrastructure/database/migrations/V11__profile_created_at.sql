@@ -0,0 +1,5 @@
+-- Existing profiles get the time of the migration
+ALTER TABLE profile
+    ADD COLUMN created_at TIMESTAMP NOT NULL DEFAULT (now() AT TIME ZONE 'utc');
+
+CREATE INDEX profile_created_at_id_index ON profile (created_at DESC, id DESC);
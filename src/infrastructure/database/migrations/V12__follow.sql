@@ -0,0 +1,19 @@
+CREATE TABLE follow
+(
+    follower_id TEXT      NOT NULL
+        CONSTRAINT follow_follower_id_fk REFERENCES profile ON DELETE CASCADE,
+    followee_id TEXT      NOT NULL
+        CONSTRAINT follow_followee_id_fk REFERENCES profile ON DELETE CASCADE,
+    created_at  TIMESTAMP NOT NULL,
+
+    CONSTRAINT follow_pk PRIMARY KEY (follower_id, followee_id),
+    CONSTRAINT follow_self_check CHECK (follower_id <> followee_id)
+);
+
+CREATE INDEX follow_followee_id_index ON follow (followee_id, created_at DESC, follower_id DESC);
+CREATE INDEX follow_follower_id_index ON follow (follower_id, created_at DESC, followee_id DESC);
+
+-- Kept up to date together with the follow table so listings don't need to count
+ALTER TABLE profile
+    ADD COLUMN follower_count  BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN following_count BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,27 @@
+CREATE TABLE profile_block
+(
+    blocker_id TEXT      NOT NULL
+        CONSTRAINT profile_block_blocker_id_fk REFERENCES profile ON DELETE CASCADE,
+    blocked_id TEXT      NOT NULL
+        CONSTRAINT profile_block_blocked_id_fk REFERENCES profile ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL,
+
+    CONSTRAINT profile_block_pk PRIMARY KEY (blocker_id, blocked_id),
+    CONSTRAINT profile_block_self_check CHECK (blocker_id <> blocked_id)
+);
+
+CREATE INDEX profile_block_blocker_id_index ON profile_block (blocker_id, created_at DESC, blocked_id DESC);
+
+CREATE TABLE profile_mute
+(
+    muter_id   TEXT      NOT NULL
+        CONSTRAINT profile_mute_muter_id_fk REFERENCES profile ON DELETE CASCADE,
+    muted_id   TEXT      NOT NULL
+        CONSTRAINT profile_mute_muted_id_fk REFERENCES profile ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL,
+
+    CONSTRAINT profile_mute_pk PRIMARY KEY (muter_id, muted_id),
+    CONSTRAINT profile_mute_self_check CHECK (muter_id <> muted_id)
+);
+
+CREATE INDEX profile_mute_muter_id_index ON profile_mute (muter_id, created_at DESC, muted_id DESC);
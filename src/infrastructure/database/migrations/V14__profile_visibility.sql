@@ -0,0 +1,8 @@
+-- Who can see the profile and some of its fields: public, signed-in or followers
+ALTER TABLE profile
+    ADD COLUMN visibility              TEXT NOT NULL DEFAULT 'public'
+        CONSTRAINT profile_visibility_check CHECK (visibility IN ('public', 'signed-in', 'followers')),
+    ADD COLUMN display_name_visibility TEXT NOT NULL DEFAULT 'public'
+        CONSTRAINT profile_display_name_visibility_check CHECK (display_name_visibility IN ('public', 'signed-in', 'followers')),
+    ADD COLUMN bio_visibility          TEXT NOT NULL DEFAULT 'public'
+        CONSTRAINT profile_bio_visibility_check CHECK (bio_visibility IN ('public', 'signed-in', 'followers'));
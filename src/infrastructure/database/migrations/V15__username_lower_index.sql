@@ -0,0 +1,3 @@
+-- Case-insensitive username lookups
+CREATE INDEX profile_lower_username_index ON profile (lower(username));
+CREATE INDEX username_history_lower_username_index ON username_history (lower(username), changed_at DESC);
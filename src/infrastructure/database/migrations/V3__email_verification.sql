@@ -0,0 +1,13 @@
+ALTER TABLE "user"
+    ADD COLUMN email_verified BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Accounts created before verification existed are considered verified
+UPDATE "user"
+SET email_verified = TRUE;
+
+CREATE TABLE email_verification_request
+(
+    token    VARCHAR(255) PRIMARY KEY NOT NULL,
+    user_id  VARCHAR(255)             NOT NULL,
+    datetime TIMESTAMP                NOT NULL
+);
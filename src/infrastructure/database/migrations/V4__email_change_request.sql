@@ -0,0 +1,8 @@
+CREATE TABLE email_change_request
+(
+    token     VARCHAR(255) PRIMARY KEY NOT NULL,
+    user_id   VARCHAR(255) UNIQUE      NOT NULL,
+    new_email TEXT                     NOT NULL
+        CONSTRAINT new_email_check CHECK (new_email = lower(new_email)),
+    datetime  TIMESTAMP                NOT NULL
+);
@@ -0,0 +1,12 @@
+ALTER TABLE "user"
+    ADD COLUMN totp_secret         BYTEA,
+    ADD COLUMN totp_enabled        BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN totp_last_used_step BIGINT;
+
+CREATE TABLE mfa_challenge
+(
+    id              VARCHAR(255) PRIMARY KEY NOT NULL,
+    user_id         VARCHAR(255)             NOT NULL,
+    datetime        TIMESTAMP                NOT NULL,
+    failed_attempts INTEGER                  NOT NULL DEFAULT 0
+);
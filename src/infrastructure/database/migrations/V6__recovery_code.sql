@@ -0,0 +1,7 @@
+CREATE TABLE recovery_code
+(
+    user_id   VARCHAR(255) NOT NULL,
+    code_hash TEXT         NOT NULL
+);
+
+CREATE INDEX recovery_code_user_id_index ON recovery_code (user_id);
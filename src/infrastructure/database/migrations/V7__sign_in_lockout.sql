@@ -0,0 +1,3 @@
+ALTER TABLE "user"
+    ADD COLUMN failed_sign_in_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN locked_until            TIMESTAMP;
@@ -0,0 +1,34 @@
+-- Canonical form of the username, see Profile::username_skeleton
+ALTER TABLE profile
+    ADD COLUMN username_skeleton TEXT;
+
+UPDATE profile
+SET username_skeleton = translate(replace(lower(username), 'rn', 'm'), '01i5', 'olls');
+
+-- Usernames used to be unique by exact match only, so look-alikes (Bob, bob, B0b) can exist.
+-- Those have to be resolved by hand before the unique index can be created, nobody is renamed silently
+DO
+$$
+    DECLARE
+        conflicts TEXT;
+    BEGIN
+        SELECT string_agg(format('%s (%s)', username, id), ', ' ORDER BY username_skeleton, username, id)
+        INTO conflicts
+        FROM (SELECT id,
+                     username,
+                     username_skeleton,
+                     count(*) OVER (PARTITION BY username_skeleton) AS profiles
+              FROM profile) AS counted
+        WHERE profiles > 1;
+
+        IF conflicts IS NOT NULL THEN
+            RAISE EXCEPTION 'Profiles with look-alike usernames: %', conflicts
+                USING HINT = 'Rename all but one profile per look-alike username and run the migration again';
+        END IF;
+    END
+$$;
+
+ALTER TABLE profile
+    ALTER COLUMN username_skeleton SET NOT NULL;
+
+CREATE UNIQUE INDEX profile_username_skeleton_uindex ON profile (username_skeleton);
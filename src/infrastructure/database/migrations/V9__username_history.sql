@@ -0,0 +1,14 @@
+ALTER TABLE profile
+    ADD COLUMN username_changed_at TIMESTAMP;
+
+-- Previous usernames, they redirect to the profile and stay claimed for a grace period
+CREATE TABLE username_history
+(
+    username          TEXT      NOT NULL,
+    username_skeleton TEXT      NOT NULL,
+    profile_id        TEXT      NOT NULL
+        CONSTRAINT username_history_profile_id_fk REFERENCES profile ON DELETE CASCADE,
+    changed_at        TIMESTAMP NOT NULL
+);
+
+CREATE INDEX username_history_username_skeleton_index ON username_history (username_skeleton);
@@ -0,0 +1,149 @@
+pub use block_repository::PostgresBlockRepository;
+
+mod block_repository {
+    use async_trait::async_trait;
+    use deadpool_postgres::Pool;
+    use figure_lib::get_tokio_postgres_executor;
+    use figure_lib::rdbs::postgres::tokio_postgres::TokioPostgresTransaction;
+    use time::{OffsetDateTime, PrimitiveDateTime};
+    use tokio_postgres::GenericClient as OtherGenericClient;
+
+    use crate::application::errors::RepositoryError;
+    use crate::application::pagination::DatedCursor;
+    use crate::application::repository_traits::read::block_repository::BlockRepository;
+    use crate::domain::Profile;
+    use crate::infrastructure::database::repositories::dated_profiles::find_dated_profiles;
+
+    #[derive(Clone)]
+    pub struct PostgresBlockRepository {
+        pool: Pool,
+    }
+
+    impl PostgresBlockRepository {
+        pub fn new(pool: Pool) -> Self {
+            Self {
+                pool,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl BlockRepository for PostgresBlockRepository {
+        async fn insert_block(&self, blocker_id: &str, blocked_id: &str) -> Result<(), RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            INSERT INTO profile_block (blocker_id, blocked_id, created_at)
+            VALUES ($1, $2, $3)
+            "#).await?;
+
+            client.execute(&statement, &[&blocker_id, &blocked_id, &Self::now()]).await?;
+
+            Ok(())
+        }
+
+        async fn delete_block(&self, blocker_id: &str, blocked_id: &str) -> Result<(), RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            DELETE FROM profile_block WHERE blocker_id = $1 AND blocked_id = $2
+            "#).await?;
+
+            match client.execute(&statement, &[&blocker_id, &blocked_id]).await? {
+                0 => Err(RepositoryError::ResourceNotFound),
+                _ => Ok(())
+            }
+        }
+
+        async fn is_blocked(&self, blocker_id: &str, blocked_id: &str) -> Result<bool, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT EXISTS(SELECT 1 FROM profile_block WHERE blocker_id = $1 AND blocked_id = $2)
+            "#).await?;
+
+            let blocked = client.query_one(&statement, &[&blocker_id, &blocked_id])
+                .await?
+                .try_get::<usize, bool>(0)?;
+
+            Ok(blocked)
+        }
+
+        async fn find_blocker_ids(&self, blocked_id: &str, profile_ids: &[String]) -> Result<Vec<String>, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT blocker_id FROM profile_block WHERE blocked_id = $1 AND blocker_id = ANY($2)
+            "#).await?;
+
+            client.query(&statement, &[&blocked_id, &profile_ids])
+                .await?
+                .into_iter()
+                .map(|row| row.try_get("blocker_id").map_err(RepositoryError::from))
+                .collect()
+        }
+
+        async fn find_blocked(&self, blocker_id: &str, after: Option<DatedCursor>, limit: i64) -> Result<Vec<(Profile, DatedCursor)>, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT profile.*, profile_block.created_at AS listed_at FROM profile_block
+            JOIN profile ON profile.id = profile_block.blocked_id
+            WHERE profile_block.blocker_id = $1
+              AND ($2::timestamp IS NULL OR (profile_block.created_at, profile_block.blocked_id) < ($2, $3))
+            ORDER BY profile_block.created_at DESC, profile_block.blocked_id DESC
+            LIMIT $4
+            "#).await?;
+
+            find_dated_profiles(client, statement, blocker_id, after, limit).await
+        }
+
+        async fn insert_mute(&self, muter_id: &str, muted_id: &str) -> Result<(), RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            INSERT INTO profile_mute (muter_id, muted_id, created_at)
+            VALUES ($1, $2, $3)
+            "#).await?;
+
+            client.execute(&statement, &[&muter_id, &muted_id, &Self::now()]).await?;
+
+            Ok(())
+        }
+
+        async fn delete_mute(&self, muter_id: &str, muted_id: &str) -> Result<(), RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            DELETE FROM profile_mute WHERE muter_id = $1 AND muted_id = $2
+            "#).await?;
+
+            match client.execute(&statement, &[&muter_id, &muted_id]).await? {
+                0 => Err(RepositoryError::ResourceNotFound),
+                _ => Ok(())
+            }
+        }
+
+        async fn find_muted(&self, muter_id: &str, after: Option<DatedCursor>, limit: i64) -> Result<Vec<(Profile, DatedCursor)>, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT profile.*, profile_mute.created_at AS listed_at FROM profile_mute
+            JOIN profile ON profile.id = profile_mute.muted_id
+            WHERE profile_mute.muter_id = $1
+              AND ($2::timestamp IS NULL OR (profile_mute.created_at, profile_mute.muted_id) < ($2, $3))
+            ORDER BY profile_mute.created_at DESC, profile_mute.muted_id DESC
+            LIMIT $4
+            "#).await?;
+
+            find_dated_profiles(client, statement, muter_id, after, limit).await
+        }
+    }
+
+    impl PostgresBlockRepository {
+        fn now() -> PrimitiveDateTime {
+            let now = OffsetDateTime::now_utc();
+            PrimitiveDateTime::new(now.date(), now.time())
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use time::PrimitiveDateTime;
+use tokio_postgres::Client;
+use tokio_postgres::types::ToSql;
+
+use crate::application::errors::RepositoryError;
+use crate::application::pagination::DatedCursor;
+use crate::domain::Profile;
+use crate::infrastructure::database::entities::DatedProfileEntity;
+
+// Runs a dated list query, its parameters are the owner of the list, the cursor (created_at and profile id) and the limit
+pub async fn find_dated_profiles(client: &Client,
+                                 statement: tokio_postgres::Statement,
+                                 profile_id: &str,
+                                 after: Option<DatedCursor>,
+                                 limit: i64) -> Result<Vec<(Profile, DatedCursor)>, RepositoryError>
+{
+    let after_created_at = after.as_ref()
+        .map(|cursor| PrimitiveDateTime::new(cursor.created_at.date(), cursor.created_at.time()));
+    let after_profile_id = after.map(|cursor| cursor.profile_id);
+
+    let parameters: [&(dyn ToSql + Sync); 4] = [&profile_id, &after_created_at, &after_profile_id, &limit];
+
+    client.query(&statement, &parameters)
+        .await?
+        .into_iter()
+        .map(|row| Ok(DatedProfileEntity::try_from(row)?.into()))
+        .collect()
+}
@@ -0,0 +1,148 @@
+pub use follow_repository::PostgresFollowRepository;
+
+mod follow_repository {
+    use async_trait::async_trait;
+    use deadpool_postgres::Pool;
+    use figure_lib::get_tokio_postgres_executor;
+    use figure_lib::rdbs::postgres::tokio_postgres::TokioPostgresTransaction;
+    use time::PrimitiveDateTime;
+    use tokio_postgres::{Client, GenericClient as OtherGenericClient};
+
+    use crate::application::errors::RepositoryError;
+    use crate::application::pagination::DatedCursor;
+    use crate::application::repository_traits::read::follow_repository::FollowRepository;
+    use crate::domain::{Follow, Profile};
+    use crate::infrastructure::database::entities::FollowEntity;
+    use crate::infrastructure::database::repositories::dated_profiles::find_dated_profiles;
+
+    #[derive(Clone)]
+    pub struct PostgresFollowRepository {
+        pool: Pool,
+    }
+
+    impl PostgresFollowRepository {
+        pub fn new(pool: Pool) -> Self {
+            Self {
+                pool,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl FollowRepository for PostgresFollowRepository {
+        async fn insert(&self, follow: &Follow) -> Result<(), RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let created_at = follow.created_at();
+
+            let statement = client.prepare(r#"
+            INSERT INTO follow (follower_id, followee_id, created_at)
+            VALUES ($1, $2, $3)
+            "#).await?;
+
+            client.execute(&statement, &[
+                &follow.follower_id(),
+                &follow.followee_id(),
+                &PrimitiveDateTime::new(created_at.date(), created_at.time())
+            ]).await?;
+
+            Self::update_counts(client, follow, 1).await
+        }
+
+        async fn find(&self, follower_id: &str, followee_id: &str) -> Result<Follow, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT * FROM follow WHERE follower_id = $1 AND followee_id = $2
+            "#).await?;
+
+            let row = match client.query_opt(&statement, &[&follower_id, &followee_id]).await? {
+                Some(row) => row,
+                None => return Err(RepositoryError::ResourceNotFound)
+            };
+
+            Ok(FollowEntity::try_from(row)?.into())
+        }
+
+        async fn delete(&self, follow: &Follow) -> Result<(), RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            DELETE FROM follow WHERE follower_id = $1 AND followee_id = $2
+            "#).await?;
+
+            let deleted = client.execute(&statement, &[
+                &follow.follower_id(),
+                &follow.followee_id()
+            ]).await?;
+
+            if deleted == 0 {
+                return Err(RepositoryError::ResourceNotFound);
+            }
+
+            Self::update_counts(client, follow, -1).await
+        }
+
+        async fn find_followers(&self, profile_id: &str, after: Option<DatedCursor>, limit: i64) -> Result<Vec<(Profile, DatedCursor)>, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT profile.*, follow.created_at AS listed_at FROM follow
+            JOIN profile ON profile.id = follow.follower_id
+            WHERE follow.followee_id = $1
+              AND ($2::timestamp IS NULL OR (follow.created_at, follow.follower_id) < ($2, $3))
+            ORDER BY follow.created_at DESC, follow.follower_id DESC
+            LIMIT $4
+            "#).await?;
+
+            find_dated_profiles(client, statement, profile_id, after, limit).await
+        }
+
+        async fn find_following(&self, profile_id: &str, after: Option<DatedCursor>, limit: i64) -> Result<Vec<(Profile, DatedCursor)>, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT profile.*, follow.created_at AS listed_at FROM follow
+            JOIN profile ON profile.id = follow.followee_id
+            WHERE follow.follower_id = $1
+              AND ($2::timestamp IS NULL OR (follow.created_at, follow.followee_id) < ($2, $3))
+            ORDER BY follow.created_at DESC, follow.followee_id DESC
+            LIMIT $4
+            "#).await?;
+
+            find_dated_profiles(client, statement, profile_id, after, limit).await
+        }
+
+        async fn find_followed_ids(&self, follower_id: &str, profile_ids: &[String]) -> Result<Vec<String>, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT followee_id FROM follow WHERE follower_id = $1 AND followee_id = ANY($2)
+            "#).await?;
+
+            client.query(&statement, &[&follower_id, &profile_ids])
+                .await?
+                .into_iter()
+                .map(|row| row.try_get("followee_id").map_err(RepositoryError::from))
+                .collect()
+        }
+    }
+
+    impl PostgresFollowRepository {
+        async fn update_counts(client: &Client, follow: &Follow, change: i64) -> Result<(), RepositoryError> {
+            let statement = client.prepare(r#"
+            UPDATE profile SET following_count = following_count + $2 WHERE id = $1
+            "#).await?;
+
+            client.execute(&statement, &[&follow.follower_id(), &change]).await?;
+
+            let statement = client.prepare(r#"
+            UPDATE profile SET follower_count = follower_count + $2 WHERE id = $1
+            "#).await?;
+
+            client.execute(&statement, &[&follow.followee_id(), &change]).await?;
+
+            Ok(())
+        }
+    }
+}
@@ -1,2 +1,5 @@
+pub mod block_repository;
+mod dated_profiles;
+pub mod follow_repository;
 pub mod profile_repository;
-pub mod user_repository;
+pub mod user_repository;
\ No newline at end of file
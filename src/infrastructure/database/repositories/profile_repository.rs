@@ -4,13 +4,17 @@ mod profile_repository {
     use async_trait::async_trait;
     use deadpool_postgres::Pool;
     use figure_lib::get_tokio_postgres_executor;
+    use figure_lib::rdbs::postgres::sea_query_misc::{Column, Table};
     use figure_lib::rdbs::postgres::tokio_postgres::TokioPostgresTransaction;
+    use sea_query::{Asterisk, Expr, Order, PostgresQueryBuilder, Query};
+    use sea_query_postgres::PostgresBinder;
+    use time::{OffsetDateTime, PrimitiveDateTime};
     use tokio_postgres::{Client, GenericClient as OtherGenericClient};
     use tokio_postgres::types::ToSql;
 
     use crate::application::errors::RepositoryError;
-    use crate::application::repository_traits::read::profile_repository::ProfileRepository;
-    use crate::domain::Profile;
+    use crate::application::repository_traits::read::profile_repository::{ListCursor, ProfileRepository, ProfileSort, ProfileVisibility, SearchCursor};
+    use crate::domain::{Profile, Visibility};
     use crate::infrastructure::database::entities::ProfileEntity;
 
     #[derive(Clone)]
@@ -32,13 +36,14 @@ mod profile_repository {
             get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
 
             let statement = client.prepare(r#"
-            INSERT INTO profile (id, username, user_id)
-            VALUES ($1, $2, $3)
+            INSERT INTO profile (id, username, username_skeleton, user_id)
+            VALUES ($1, $2, $3, $4)
             "#).await?;
 
             client.execute(&statement, &[
                 &profile.get_id(),
                 &profile.get_username(),
+                &profile.get_username_skeleton(),
                 &profile.get_user_id()
             ]).await?;
 
@@ -57,6 +62,50 @@ mod profile_repository {
             ]).await
         }
 
+        async fn find_by_username(&self, username: &str) -> Result<Profile, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            // Usernames that only differ in case could exist before skeletons were unique,
+            // an exact match wins and otherwise the oldest profile
+            let statement = client.prepare(r#"
+            SELECT * FROM profile WHERE lower(username) = lower($1)
+            ORDER BY username = $1 DESC, created_at, id
+            LIMIT 1
+            "#).await?;
+
+            Self::find_one(client, statement, &[
+                &username,
+            ]).await
+        }
+
+        async fn find_by_username_skeleton(&self, skeleton: &str) -> Result<Profile, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT * FROM profile WHERE username_skeleton = $1
+            "#).await?;
+
+            Self::find_one(client, statement, &[
+                &skeleton,
+            ]).await
+        }
+
+        async fn find_by_ids(&self, profile_ids: &[String]) -> Result<Vec<Profile>, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT * FROM profile WHERE id = ANY($1)
+            "#).await?;
+
+            let rows = client.query(&statement, &[
+                &profile_ids,
+            ]).await?;
+
+            rows.into_iter()
+                .map(|row| Ok(ProfileEntity::try_from(row)?.into()))
+                .collect()
+        }
+
         async fn find_by_user_id(&self, user_id: String) -> Result<Profile, RepositoryError> {
             get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
 
@@ -69,24 +118,243 @@ mod profile_repository {
             ]).await
         }
 
-        async fn update_profile_by_id(&self, profile_id: String, display_name: Option<String>, bio: Option<String>) -> Result<(), RepositoryError> {
+        async fn update_profile_by_id(&self, profile_id: String, display_name: Option<String>, bio: Option<String>, visibility: ProfileVisibility) -> Result<(), RepositoryError> {
             get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
 
             let statement = client.prepare(r#"
             UPDATE profile
             SET display_name = $1, bio = $2,
-            WHERE id = $3
+                visibility = coalesce($3, visibility),
+                display_name_visibility = coalesce($4, display_name_visibility),
+                bio_visibility = coalesce($5, bio_visibility)
+            WHERE id = $6
             "#).await?;
 
             client.execute(&statement, &[
                 &display_name,
                 &bio,
+                &visibility.profile.map(Visibility::as_str),
+                &visibility.display_name.map(Visibility::as_str),
+                &visibility.bio.map(Visibility::as_str),
+                &profile_id
+            ]).await?;
+
+            Ok(())
+        }
+
+        async fn change_username(&self, profile: &Profile, old_username: &str) -> Result<(), RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let changed_at = profile.username_changed_at
+                .map(|datetime| PrimitiveDateTime::new(datetime.date(), datetime.time()));
+
+            let statement = client.prepare(r#"
+            UPDATE profile
+            SET username = $1, username_skeleton = $2, username_changed_at = $3
+            WHERE id = $4
+            "#).await?;
+
+            client.execute(&statement, &[
+                &profile.get_username(),
+                &profile.get_username_skeleton(),
+                &changed_at,
+                &profile.get_id()
+            ]).await?;
+
+            let statement = client.prepare(r#"
+            INSERT INTO username_history (username, username_skeleton, profile_id, changed_at)
+            VALUES ($1, $2, $3, $4)
+            "#).await?;
+
+            client.execute(&statement, &[
+                &old_username,
+                &Profile::username_skeleton(old_username),
+                &profile.get_id(),
+                &changed_at
+            ]).await?;
+
+            Ok(())
+        }
+
+        async fn find_by_previous_username(&self, username: &str, changed_after: OffsetDateTime) -> Result<Profile, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT profile.* FROM profile
+            JOIN username_history ON username_history.profile_id = profile.id
+            WHERE lower(username_history.username) = lower($1) AND username_history.changed_at > $2
+            ORDER BY username_history.changed_at DESC
+            LIMIT 1
+            "#).await?;
+
+            Self::find_one(client, statement, &[
+                &username,
+                &PrimitiveDateTime::new(changed_after.date(), changed_after.time()),
+            ]).await
+        }
+
+        async fn find_by_previous_username_skeleton(&self, skeleton: &str, changed_after: OffsetDateTime) -> Result<Profile, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT profile.* FROM profile
+            JOIN username_history ON username_history.profile_id = profile.id
+            WHERE username_history.username_skeleton = $1 AND username_history.changed_at > $2
+            ORDER BY username_history.changed_at DESC
+            LIMIT 1
+            "#).await?;
+
+            Self::find_one(client, statement, &[
+                &skeleton,
+                &PrimitiveDateTime::new(changed_after.date(), changed_after.time()),
+            ]).await
+        }
+
+        async fn update_profile_picture_by_id(&self, profile_id: String, profile_picture: Option<String>) -> Result<(), RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            UPDATE profile
+            SET profile_picture = $1
+            WHERE id = $2
+            "#).await?;
+
+            client.execute(&statement, &[
+                &profile_picture,
                 &profile_id
             ]).await?;
 
             Ok(())
         }
 
+        async fn update_banner_by_id(&self, profile_id: String, banner: Option<String>) -> Result<(), RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            UPDATE profile
+            SET banner = $1
+            WHERE id = $2
+            "#).await?;
+
+            client.execute(&statement, &[
+                &banner,
+                &profile_id
+            ]).await?;
+
+            Ok(())
+        }
+
+        async fn search(&self, query: &str, after: Option<SearchCursor>, limit: i64) -> Result<Vec<(Profile, SearchCursor)>, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            // Short queries barely have any trigrams, the prefix match covers those.
+            // Only public profiles are searchable, display names only when anyone can see them
+            let statement = client.prepare(r#"
+            SELECT * FROM (
+                SELECT profile.*,
+                       greatest(
+                           similarity(username, $1),
+                           CASE WHEN display_name_visibility = 'public'
+                                THEN similarity(coalesce(display_name, ''), $1) ELSE 0 END
+                       ) AS rank
+                FROM profile
+                WHERE visibility = 'public'
+                  AND (username % $1
+                       OR (display_name % $1 AND display_name_visibility = 'public')
+                       OR username ILIKE $2)
+            ) AS results
+            WHERE $3::real IS NULL OR (rank, id) < ($3, $4)
+            ORDER BY rank DESC, id DESC
+            LIMIT $5
+            "#).await?;
+
+            let prefix = format!("{}%", query
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_"));
+
+            let rows = client.query(&statement, &[
+                &query,
+                &prefix,
+                &after.as_ref().map(|cursor| cursor.rank),
+                &after.as_ref().map(|cursor| cursor.id.clone()),
+                &limit,
+            ]).await?;
+
+            rows.into_iter()
+                .map(|row| {
+                    let rank: f32 = row.try_get("rank")?;
+                    let entity = ProfileEntity::try_from(row)?;
+                    let profile: Profile = entity.into();
+
+                    let cursor = SearchCursor {
+                        rank,
+                        id: profile.get_id(),
+                    };
+
+                    Ok((profile, cursor))
+                })
+                .collect()
+        }
+
+        async fn list(&self, sort: ProfileSort, after: Option<ListCursor>, limit: i64) -> Result<Vec<(Profile, ListCursor)>, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let mut select = Query::select();
+            let mut statement = select
+                .column(Asterisk)
+                .from(Table("profile"))
+                .and_where(Expr::col(Column("visibility")).eq(Visibility::Public.as_str()))
+                .limit(limit as u64);
+
+            statement = match sort {
+                ProfileSort::Newest => statement
+                    .order_by(Column("created_at"), Order::Desc)
+                    .order_by(Column("id"), Order::Desc),
+                ProfileSort::Username => statement
+                    .order_by(Column("username"), Order::Asc),
+            };
+
+            match after {
+                Some(ListCursor::Newest { created_at, id }) => {
+                    let created_at = PrimitiveDateTime::new(created_at.date(), created_at.time());
+
+                    statement = statement.and_where(
+                        Expr::tuple([Expr::col(Column("created_at")).into(), Expr::col(Column("id")).into()])
+                            .lt(Expr::tuple([Expr::val(created_at).into(), Expr::val(id).into()]))
+                    );
+                }
+                Some(ListCursor::Username { username }) => {
+                    statement = statement.and_where(Expr::col(Column("username")).gt(username));
+                }
+                None => {}
+            }
+
+            let (sql, values) = statement.build_postgres(PostgresQueryBuilder);
+
+            let rows = client.query(&sql, &values.as_params()).await?;
+
+            rows.into_iter()
+                .map(|row| {
+                    let created_at = row.try_get::<_, PrimitiveDateTime>("created_at")?.assume_utc();
+                    let entity = ProfileEntity::try_from(row)?;
+                    let profile: Profile = entity.into();
+
+                    let cursor = match sort {
+                        ProfileSort::Newest => ListCursor::Newest {
+                            created_at,
+                            id: profile.get_id(),
+                        },
+                        ProfileSort::Username => ListCursor::Username {
+                            username: profile.get_username().to_string(),
+                        },
+                    };
+
+                    Ok((profile, cursor))
+                })
+                .collect()
+        }
+
         async fn get_total_profiles_count(&self) -> Result<i64, RepositoryError> {
             get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
 
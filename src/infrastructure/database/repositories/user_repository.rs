@@ -5,13 +5,14 @@ use figure_lib::rdbs::postgres::sea_query_misc::{Column, Table};
 use figure_lib::rdbs::postgres::tokio_postgres::TokioPostgresTransaction;
 use sea_query::{PostgresQueryBuilder, Query};
 use sea_query_postgres::PostgresBinder;
-use tokio_postgres::{GenericClient, Row};
+use time::PrimitiveDateTime;
+use tokio_postgres::{Client, GenericClient, Row};
 
 use crate::application::errors::RepositoryError;
 use crate::application::repository_traits::read::user_repository::UserRepository;
 use crate::domain::User;
-use crate::domain::user::user::ResetPasswordRequest;
-use crate::infrastructure::database::entities::{ResetPasswordRequestEntity, UserEntity};
+use crate::domain::user::user::{EmailChangeRequest, EmailVerificationRequest, MfaChallenge, RecoveryCode, ResetPasswordRequest};
+use crate::infrastructure::database::entities::{EmailChangeRequestEntity, EmailVerificationRequestEntity, MfaChallengeEntity, RecoveryCodeEntity, ResetPasswordRequestEntity, UserEntity};
 
 #[derive(Clone)]
 pub struct TokioPostgresUserRepository {
@@ -30,8 +31,8 @@ impl TokioPostgresUserRepository {
             get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
 
             let user_statement = client.prepare(r#"
-            INSERT INTO "user" (id, email, password, role)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO "user" (id, email, password, role, email_verified)
+            VALUES ($1, $2, $3, $4, $5)
             "#).await?;
 
             client.execute(&user_statement, &[
@@ -39,6 +40,7 @@ impl TokioPostgresUserRepository {
                 &user.get_email(),
                 &user.get_password(),
                 &user.get_role(),
+                &user.is_email_verified(),
             ]).await?;
 
             Ok(())
@@ -49,7 +51,8 @@ impl TokioPostgresUserRepository {
 
             let statement = client.prepare(r#"
             SELECT
-            id, email, password, role
+            id, email, password, role, email_verified, failed_sign_in_attempts, locked_until,
+            totp_secret, totp_enabled, totp_last_used_step
             FROM "user"
             WHERE email = $1
             FOR UPDATE
@@ -60,23 +63,7 @@ impl TokioPostgresUserRepository {
 
             let entity = UserEntity::try_from(row)?;
 
-            let password_resets_statement = client.prepare(r#"
-            SELECT user_id, token, datetime FROM password_reset_request
-            WHERE user_id = $1
-            ORDER BY password_reset_request.datetime
-            FOR UPDATE
-            "#).await?;
-
-            let password_reset_requests_rows = client
-                .query(&password_resets_statement, &[&entity.id])
-                .await?;
-
-            let password_reset_requests = Self
-            ::process_password_reset_request_rows(password_reset_requests_rows).await?;
-
-            let user = entity.into_user(password_reset_requests);
-
-            Ok(user)
+            Self::load_user(client, entity).await
         }
 
         async fn find_by_id(&self, user_id: &str) -> Result<User, RepositoryError> {
@@ -84,7 +71,8 @@ impl TokioPostgresUserRepository {
 
             let statement = client.prepare(r#"
             SELECT
-            id, email, password, role
+            id, email, password, role, email_verified, failed_sign_in_attempts, locked_until,
+            totp_secret, totp_enabled, totp_last_used_step
             FROM "user"
             WHERE id = $1
             FOR UPDATE
@@ -92,27 +80,10 @@ impl TokioPostgresUserRepository {
 
             let row = client.query_opt(&statement, &[&user_id]).await?
                 .ok_or_else(|| RepositoryError::ResourceNotFound)?;
-            ;
 
             let entity = UserEntity::try_from(row)?;
 
-            let password_resets_statement = client.prepare(r#"
-            SELECT user_id, token, datetime FROM password_reset_request
-            WHERE user_id = $1
-            ORDER BY password_reset_request.datetime
-            FOR UPDATE
-            "#).await?;
-
-            let password_reset_requests_rows = client
-                .query(&password_resets_statement, &[&user_id])
-                .await?;
-
-            let password_reset_requests = Self
-            ::process_password_reset_request_rows(password_reset_requests_rows).await?;
-
-            let user = entity.into_user(password_reset_requests);
-
-            Ok(user)
+            Self::load_user(client, entity).await
         }
 
         async fn update(&self, user: &User) -> Result<(), RepositoryError> {
@@ -120,15 +91,27 @@ impl TokioPostgresUserRepository {
 
             let statement = client.prepare(r#"
             UPDATE "user"
-            SET email = $2, password = $3, role = $4
+            SET email = $2, password = $3, role = $4, email_verified = $5,
+            totp_secret = $6, totp_enabled = $7, totp_last_used_step = $8,
+            failed_sign_in_attempts = $9, locked_until = $10
             WHERE id = $1
             "#).await?;
 
+            let totp = user.totp();
+            let locked_until = user.locked_until()
+                .map(|datetime| PrimitiveDateTime::new(datetime.date(), datetime.time()));
+
             client.execute(&statement, &[
                 &user.get_id(),
                 &user.get_email(),
                 &user.get_password(),
-                &user.get_role()
+                &user.get_role(),
+                &user.is_email_verified(),
+                &totp.map(|totp| totp.encrypted_secret()),
+                &totp.is_some_and(|totp| totp.enabled()),
+                &totp.and_then(|totp| totp.last_used_step()),
+                &user.failed_sign_in_attempts(),
+                &locked_until,
             ]).await?;
 
             let statement = client.prepare(r#"
@@ -158,6 +141,111 @@ impl TokioPostgresUserRepository {
                 client.execute(&statement2, &values.as_params()).await?;
             }
 
+            let statement = client.prepare(r#"
+            DELETE FROM email_verification_request
+            WHERE user_id = $1
+            "#).await?;
+
+            client.execute(&statement, &[&user.get_id()]).await?;
+
+            if user.email_verification_requests().len() > 0 {
+                let mut insert = Query::insert();
+                let mut statement = insert.into_table(Table("email_verification_request"))
+                    .columns([Column("user_id"), Column("token"), Column("datetime")]);
+
+                for email_verification_request in user.email_verification_requests() {
+                    statement = statement.values(
+                        [
+                            user.get_id().clone().into(),
+                            email_verification_request.token().into(),
+                            email_verification_request.datetime().into()
+                        ]
+                    )?;
+                }
+
+                let (statement2, values) = statement.build_postgres(PostgresQueryBuilder);
+
+                client.execute(&statement2, &values.as_params()).await?;
+            }
+
+            let statement = client.prepare(r#"
+            DELETE FROM email_change_request
+            WHERE user_id = $1
+            "#).await?;
+
+            client.execute(&statement, &[&user.get_id()]).await?;
+
+            if let Some(email_change_request) = user.email_change_request() {
+                let datetime = email_change_request.datetime();
+                let datetime = PrimitiveDateTime::new(datetime.date(), datetime.time());
+
+                let statement = client.prepare(r#"
+                INSERT INTO email_change_request (token, user_id, new_email, datetime)
+                VALUES ($1, $2, $3, $4)
+                "#).await?;
+
+                client.execute(&statement, &[
+                    &email_change_request.token(),
+                    &user.get_id(),
+                    &email_change_request.new_email(),
+                    &datetime,
+                ]).await?;
+            }
+
+            let statement = client.prepare(r#"
+            DELETE FROM mfa_challenge
+            WHERE user_id = $1
+            "#).await?;
+
+            client.execute(&statement, &[&user.get_id()]).await?;
+
+            if user.mfa_challenges().len() > 0 {
+                let mut insert = Query::insert();
+                let mut statement = insert.into_table(Table("mfa_challenge"))
+                    .columns([Column("id"), Column("user_id"), Column("datetime"), Column("failed_attempts")]);
+
+                for mfa_challenge in user.mfa_challenges() {
+                    statement = statement.values(
+                        [
+                            mfa_challenge.id().into(),
+                            user.get_id().into(),
+                            mfa_challenge.datetime().into(),
+                            mfa_challenge.failed_attempts().into()
+                        ]
+                    )?;
+                }
+
+                let (statement2, values) = statement.build_postgres(PostgresQueryBuilder);
+
+                client.execute(&statement2, &values.as_params()).await?;
+            }
+
+            let statement = client.prepare(r#"
+            DELETE FROM recovery_code
+            WHERE user_id = $1
+            "#).await?;
+
+            client.execute(&statement, &[&user.get_id()]).await?;
+
+            if user.recovery_codes().len() > 0 {
+                let mut insert = Query::insert();
+                let mut statement = insert.into_table(Table("recovery_code"))
+                    .columns([Column("user_id"), Column("code_hash")]);
+
+                for recovery_code in user.recovery_codes() {
+                    statement = statement.values(
+                        [
+                            user.get_id().into(),
+                            recovery_code.hash().into()
+                        ]
+                    )?;
+                }
+
+                let (statement2, values) = statement.build_postgres(PostgresQueryBuilder);
+
+                client.execute(&statement2, &values.as_params()).await?;
+            }
+
             Ok(())
         }
 
@@ -165,7 +253,8 @@ impl TokioPostgresUserRepository {
             get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
 
             let statement = client.prepare(r#"
-            SELECT id, email, password, role
+            SELECT id, email, password, role, email_verified, failed_sign_in_attempts, locked_until,
+            totp_secret, totp_enabled, totp_last_used_step
             FROM "user"
             INNER JOIN password_reset_request ON "user".id = password_reset_request.user_id
             WHERE password_reset_request.token = $1
@@ -177,26 +266,140 @@ impl TokioPostgresUserRepository {
                 .ok_or_else(|| RepositoryError::ResourceNotFound)?;
             let entity = UserEntity::try_from(row)?;
 
-            let password_resets_statement = client.prepare(r#"
-            SELECT user_id, token, datetime FROM password_reset_request
-            WHERE user_id = $1
+            Self::load_user(client, entity).await
+        }
+
+        async fn find_by_email_verification_token(&self, token: &str) -> Result<User, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT id, email, password, role, email_verified, failed_sign_in_attempts, locked_until,
+            totp_secret, totp_enabled, totp_last_used_step
+            FROM "user"
+            INNER JOIN email_verification_request ON "user".id = email_verification_request.user_id
+            WHERE email_verification_request.token = $1
             FOR UPDATE
             "#).await?;
 
-            let password_reset_requests_rows = client
-                .query(&password_resets_statement, &[&entity.id])
-                .await?;
+            let row = client.query_opt(&statement, &[&token]).await?
+                .ok_or_else(|| RepositoryError::ResourceNotFound)?;
+            let entity = UserEntity::try_from(row)?;
 
-            let password_reset_requests = Self
-            ::process_password_reset_request_rows(password_reset_requests_rows).await?;
+            Self::load_user(client, entity).await
+        }
+
+        async fn find_by_email_change_token(&self, token: &str) -> Result<User, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT id, email, password, role, email_verified, failed_sign_in_attempts, locked_until,
+            totp_secret, totp_enabled, totp_last_used_step
+            FROM "user"
+            INNER JOIN email_change_request ON "user".id = email_change_request.user_id
+            WHERE email_change_request.token = $1
+            FOR UPDATE
+            "#).await?;
 
-            let user = entity.into_user(password_reset_requests);
+            let row = client.query_opt(&statement, &[&token]).await?
+                .ok_or_else(|| RepositoryError::ResourceNotFound)?;
+            let entity = UserEntity::try_from(row)?;
 
-            Ok(user)
+            Self::load_user(client, entity).await
+        }
+
+        async fn find_by_mfa_challenge_id(&self, challenge_id: &str) -> Result<User, RepositoryError> {
+            get_tokio_postgres_executor!(|| async { self.pool.get().await }, client, txn, cnn, lock);
+
+            let statement = client.prepare(r#"
+            SELECT "user".id, email, password, role, email_verified, failed_sign_in_attempts, locked_until,
+            totp_secret, totp_enabled, totp_last_used_step
+            FROM "user"
+            INNER JOIN mfa_challenge ON "user".id = mfa_challenge.user_id
+            WHERE mfa_challenge.id = $1
+            FOR UPDATE
+            "#).await?;
+
+            let row = client.query_opt(&statement, &[&challenge_id]).await?
+                .ok_or_else(|| RepositoryError::ResourceNotFound)?;
+            let entity = UserEntity::try_from(row)?;
+
+            Self::load_user(client, entity).await
         }
     }
 
 impl TokioPostgresUserRepository {
+    // Loads the child rows of the user aggregate
+    async fn load_user(client: &Client, entity: UserEntity) -> Result<User, RepositoryError> {
+        let password_resets_statement = client.prepare(r#"
+        SELECT user_id, token, datetime FROM password_reset_request
+        WHERE user_id = $1
+        ORDER BY password_reset_request.datetime
+        FOR UPDATE
+        "#).await?;
+
+        let password_reset_requests_rows = client
+            .query(&password_resets_statement, &[&entity.id])
+            .await?;
+
+        let password_reset_requests = Self
+        ::process_password_reset_request_rows(password_reset_requests_rows).await?;
+
+        let email_verifications_statement = client.prepare(r#"
+        SELECT user_id, token, datetime FROM email_verification_request
+        WHERE user_id = $1
+        ORDER BY email_verification_request.datetime
+        FOR UPDATE
+        "#).await?;
+
+        let email_verification_requests_rows = client
+            .query(&email_verifications_statement, &[&entity.id])
+            .await?;
+
+        let email_verification_requests = Self
+        ::process_email_verification_request_rows(email_verification_requests_rows).await?;
+
+        let email_change_statement = client.prepare(r#"
+        SELECT user_id, token, new_email, datetime FROM email_change_request
+        WHERE user_id = $1
+        FOR UPDATE
+        "#).await?;
+
+        let email_change_request = client
+            .query_opt(&email_change_statement, &[&entity.id])
+            .await?
+            .map(|row| EmailChangeRequestEntity::try_from(row).map(EmailChangeRequest::from))
+            .transpose()?;
+
+        let mfa_challenges_statement = client.prepare(r#"
+        SELECT id, user_id, datetime, failed_attempts FROM mfa_challenge
+        WHERE user_id = $1
+        FOR UPDATE
+        "#).await?;
+
+        let mfa_challenges = client
+            .query(&mfa_challenges_statement, &[&entity.id])
+            .await?
+            .into_iter()
+            .map(|row| MfaChallengeEntity::try_from(row).map(MfaChallenge::from))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let recovery_codes_statement = client.prepare(r#"
+        SELECT user_id, code_hash FROM recovery_code
+        WHERE user_id = $1
+        FOR UPDATE
+        "#).await?;
+
+        let recovery_codes = client
+            .query(&recovery_codes_statement, &[&entity.id])
+            .await?
+            .into_iter()
+            .map(|row| RecoveryCodeEntity::try_from(row).map(RecoveryCode::from))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entity.into_user(password_reset_requests, email_verification_requests, email_change_request,
+                            mfa_challenges, recovery_codes))
+    }
+
     async fn process_password_reset_request_rows(rows: Vec<Row>) -> Result<Vec<ResetPasswordRequest>, RepositoryError> {
         let password_reset_requests = rows
             .into_iter()
@@ -211,4 +414,12 @@ impl TokioPostgresUserRepository {
 
         Ok(vec)
     }
-}
\ No newline at end of file
+
+    async fn process_email_verification_request_rows(rows: Vec<Row>) -> Result<Vec<EmailVerificationRequest>, RepositoryError> {
+        rows
+            .into_iter()
+            .map(|row| EmailVerificationRequestEntity::try_from(row)
+                .map(EmailVerificationRequest::from))
+            .collect()
+    }
+}
@@ -1 +1,2 @@
-pub mod session_layer;
\ No newline at end of file
+pub mod session_layer;
+pub mod rate_limit_layer;
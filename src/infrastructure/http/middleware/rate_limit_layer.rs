@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::{ConnectInfo, State};
+use axum::middleware::Next;
+use axum_core::extract::Request;
+use axum_core::response::{IntoResponse, Response};
+use http::{HeaderValue, StatusCode};
+use http::header::RETRY_AFTER;
+use redis::aio::ConnectionManager;
+use time::OffsetDateTime;
+use tracing::log::warn;
+
+use crate::application::routes::ConnectionInfo;
+
+// Fixed window counters per route and IP, kept in Redis so every instance shares them.
+// Falls back to counting in memory when Redis isn't configured or can't be reached.
+#[derive(Clone)]
+pub struct RateLimiter {
+    redis: Option<ConnectionManager>,
+    memory: Arc<Mutex<HashMap<String, (i64, u64)>>>,
+}
+
+#[derive(Clone)]
+pub struct RateLimitRule {
+    pub limiter: RateLimiter,
+    pub route: &'static str,
+    pub max_requests: u64,
+    pub window: Duration,
+}
+
+impl RateLimiter {
+    pub fn new(redis: Option<ConnectionManager>) -> Self {
+        Self {
+            redis,
+            memory: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn rule(&self, route: &'static str, max_requests: u64, window: Duration) -> RateLimitRule {
+        RateLimitRule {
+            limiter: self.clone(),
+            route,
+            max_requests,
+            window,
+        }
+    }
+
+    // Returns the amount of seconds to wait when the limit is exceeded
+    pub async fn check(&self, route: &str, client: &str, max_requests: u64, window: Duration) -> Option<u64> {
+        let window_seconds = window.as_secs().max(1) as i64;
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let window_index = now / window_seconds;
+        let retry_after = ((window_index + 1) * window_seconds - now) as u64;
+
+        let key = format!("rate_limit:{route}:{client}:{window_index}");
+
+        let count = match self.increment_redis(&key, window_seconds).await {
+            Some(count) => count,
+            None => self.increment_memory(&key, window_index)
+        };
+
+        (count > max_requests).then_some(retry_after)
+    }
+
+    async fn increment_redis(&self, key: &str, window_seconds: i64) -> Option<u64> {
+        let mut connection = self.redis.clone()?;
+
+        let result: Result<(u64,), _> = redis::pipe()
+            .atomic()
+            .cmd("INCR").arg(key)
+            .cmd("EXPIRE").arg(key).arg(window_seconds).ignore()
+            .query_async(&mut connection)
+            .await;
+
+        match result {
+            Ok((count,)) => Some(count),
+            Err(e) => {
+                warn!("Rate limiter couldn't reach Redis, counting in memory: {}", e);
+                None
+            }
+        }
+    }
+
+    fn increment_memory(&self, key: &str, window_index: i64) -> u64 {
+        let mut memory = self.memory.lock().unwrap();
+
+        // Drop counters of previous windows
+        if memory.len() > 10_000 {
+            memory.retain(|_, (index, _)| *index == window_index);
+        }
+
+        let entry = memory.entry(key.to_string()).or_insert((window_index, 0));
+        entry.1 += 1;
+        entry.1
+    }
+}
+
+pub async fn rate_limit(State(rule): State<RateLimitRule>, req: Request, next: Next) -> Response {
+    let client = match req.extensions().get::<ConnectInfo<ConnectionInfo>>() {
+        Some(ConnectInfo(info)) => info.remote_addr.ip().to_string(),
+        None => return StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    };
+
+    if let Some(retry_after) = rule.limiter.check(rule.route, &client, rule.max_requests, rule.window).await {
+        let mut response = StatusCode::TOO_MANY_REQUESTS.into_response();
+        response.headers_mut().insert(RETRY_AFTER, HeaderValue::from(retry_after));
+        return response;
+    }
+
+    next.run(req).await
+}
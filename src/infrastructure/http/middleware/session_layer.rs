@@ -1,23 +1,80 @@
+use std::sync::Arc;
+
+use axum::extract::State;
 use axum::middleware::Next;
 use axum_core::extract::Request;
 use axum_core::response::{IntoResponse, Response};
-use http::{HeaderValue, StatusCode};
+use hmac::{Hmac, Mac};
+use http::{HeaderMap, StatusCode};
+use sha2::Sha256;
+use thiserror::Error;
+use time::OffsetDateTime;
 
 use crate::infrastructure::session::{Session, SessionOption};
 
-pub async fn session_extension(mut req: Request, next: Next) -> Response {
-    let headers = req.headers();
+// The gateway signs the session headers with HMAC-SHA256 over "user_id\nprofile_id\nsession_expires",
+// hex encoded in the session_signature header.
+#[derive(Clone)]
+pub enum SessionHeaderVerifier {
+    Signed { secret: Arc<Vec<u8>> },
+    // Trusts the headers as-is, only meant for local development without a gateway
+    Trusted,
+}
+
+#[derive(Debug, Error)]
+enum SessionHeaderError {
+    #[error(transparent)]
+    UnexpectedError(anyhow::Error),
+    #[error("invalid-session-headers")]
+    Invalid,
+}
+
+impl SessionHeaderVerifier {
+    pub fn signed(secret: &str) -> Self {
+        Self::Signed {
+            secret: Arc::new(secret.as_bytes().to_vec()),
+        }
+    }
+
+    fn verify(&self, headers: &HeaderMap) -> Result<SessionOption, SessionHeaderError> {
+        let (user_id, profile_id) = match (header_str(headers, "user_id")?, header_str(headers, "profile_id")?) {
+            (Some(user_id), Some(profile_id)) => (user_id, profile_id),
+            (None, None) => return Ok(SessionOption::new()),
+            _ => return Err(SessionHeaderError::Invalid)
+        };
+
+        if let Self::Signed { secret } = self {
+            let expires = header_str(headers, "session_expires")?
+                .and_then(|expires| expires.parse::<i64>().ok())
+                .ok_or(SessionHeaderError::Invalid)?;
 
-    let user_id = headers.get("user_id");
-    let profile_id = headers.get("profile_id");
+            let signature = header_str(headers, "session_signature")?
+                .and_then(|signature| hex::decode(signature).ok())
+                .ok_or(SessionHeaderError::Invalid)?;
 
-    let session = handle_session_headers(user_id, profile_id);
+            if expires < OffsetDateTime::now_utc().unix_timestamp() {
+                return Err(SessionHeaderError::Invalid);
+            }
 
-    if session.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+                .map_err(|e| SessionHeaderError::UnexpectedError(e.into()))?;
+            mac.update(format!("{user_id}\n{profile_id}\n{expires}").as_bytes());
+
+            // Constant time comparison
+            mac.verify_slice(&signature)
+                .map_err(|_| SessionHeaderError::Invalid)?;
+        }
+
+        Ok(SessionOption::from(Session::new(user_id.to_string(), profile_id.to_string())))
     }
+}
 
-    let session = session.unwrap();
+pub async fn session_extension(State(verifier): State<SessionHeaderVerifier>, mut req: Request, next: Next) -> Response {
+    let session = match verifier.verify(req.headers()) {
+        Ok(session) => session,
+        Err(SessionHeaderError::Invalid) => return StatusCode::UNAUTHORIZED.into_response(),
+        Err(SessionHeaderError::UnexpectedError(_)) => return StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    };
 
     req
         .extensions_mut()
@@ -33,13 +90,9 @@ pub async fn session_extension(mut req: Request, next: Next) -> Response {
     response
 }
 
-fn handle_session_headers(user_id: Option<&HeaderValue>, profile_id: Option<&HeaderValue>) -> Result<SessionOption, anyhow::Error> {
-    match (user_id, profile_id) {
-        (Some(user_id), Some(profile_id)) => {
-            Ok(SessionOption::from(Session::new(user_id.to_str()?.to_string(),
-                                                profile_id.to_str()?.to_string())))
-        }
-
-        _ => Ok(SessionOption::new())
-    }
-}
\ No newline at end of file
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Result<Option<&'a str>, SessionHeaderError> {
+    headers.get(name)
+        .map(|value| value.to_str())
+        .transpose()
+        .map_err(|e| SessionHeaderError::UnexpectedError(e.into()))
+}
@@ -9,6 +9,7 @@ use http::Method;
 use tower_cookies::CookieManagerLayer;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 
+use crate::application::routes::internal_routes::internal_router;
 use crate::application::routes::profile_routes::profile_router;
 use crate::application::routes::user_routes::user_router;
 use crate::application::state::ServerState;
@@ -20,12 +21,12 @@ pub fn create_router(server_state: Arc<ServerState>, cors: &String) -> Result<Ro
 
     let router = Router::new()
         .merge(profile_router())
-        .merge(user_router())
+        .merge(user_router(&server_state.rate_limiter))
 
         .route("/healthcheck", get(healthcheck))
 
 
-        .layer(middleware::from_fn(session_extension))
+        .layer(middleware::from_fn_with_state(server_state.session_header_verifier.clone(), session_extension))
         .layer(CookieManagerLayer::new())
         .layer(cors_layer)
         .with_state(server_state)
@@ -35,10 +36,19 @@ pub fn create_router(server_state: Arc<ServerState>, cors: &String) -> Result<Ro
     Ok(router)
 }
 
+// No session, cookie or CORS handling, only other services call these routes
+pub fn create_internal_router(server_state: Arc<ServerState>) -> Router {
+    Router::new()
+        .merge(internal_router())
+        .with_state(server_state)
+        .layer(http_tracing_layer())
+        .layer(CorrelationLayer)
+}
+
 fn create_cors_layer<T: Into<AllowOrigin>>(origins: T) -> CorsLayer {
     CorsLayer::new()
         .allow_credentials(true)
-        .allow_methods([Method::GET, Method::POST])
+        .allow_methods([Method::GET, Method::POST, Method::DELETE])
         .allow_headers([ACCEPT, CONTENT_TYPE])
         .allow_origin(origins)
 }
\ No newline at end of file
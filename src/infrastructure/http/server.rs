@@ -1,3 +1,4 @@
+use std::future::IntoFuture;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Instant;
@@ -7,7 +8,7 @@ use tracing::log::info;
 use crate::application::environment::Environment;
 use crate::application::routes::ConnectionInfo;
 use crate::application::state::ServerState;
-use crate::infrastructure::http::router::create_router;
+use crate::infrastructure::http::router::{create_internal_router, create_router};
 
 pub async fn start_http_server(env: &Environment, state: Arc<ServerState>) -> Result<(), anyhow::Error> {
     let time_to_start = Instant::now();
@@ -15,19 +16,23 @@ pub async fn start_http_server(env: &Environment, state: Arc<ServerState>) -> Re
     info!("Allowed origin (CORS): {}", env.origin);
 
     info!("Setting up routes and layers...");
-    let router = create_router(state, &env.origin)?;
+    let router = create_router(state.clone(), &env.origin)?;
+    let internal_router = create_internal_router(state);
 
     let server_port = env.server_port;
     let addr = SocketAddr::from(([0, 0, 0, 0], server_port));
 
     let socket = tokio::net::TcpListener::bind(addr).await?;
+    let internal_socket = tokio::net::TcpListener::bind(env.internal_server_address).await?;
 
     info!("Starting Axum...");
     let axum_server = axum::serve(socket, router.into_make_service_with_connect_info::<ConnectionInfo>());
+    let internal_axum_server = axum::serve(internal_socket, internal_router.into_make_service());
 
     info!("Server is up at port {server_port}");
+    info!("Internal server is up at {}", env.internal_server_address);
     info!("Ready to serve in {}ms", time_to_start.elapsed().as_millis());
 
-    axum_server.await?;
+    tokio::try_join!(axum_server.into_future(), internal_axum_server.into_future())?;
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,107 @@
+use std::io::Cursor;
+
+use error_conversion_macro::ErrorEnum;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits};
+use image::imageops::FilterType;
+use thiserror::Error;
+
+// Decoded images larger than this are rejected before any pixels are allocated
+const MAX_DIMENSION: u32 = 8192;
+
+// Uploads are always re-encoded to this format
+pub const OUTPUT_CONTENT_TYPE: &str = "image/webp";
+pub const OUTPUT_EXTENSION: &str = "webp";
+
+#[derive(Debug, Error, ErrorEnum)]
+pub enum ImageProcessingError {
+    #[error(transparent)]
+    UnexpectedError(anyhow::Error),
+    #[error("unsupported-image-type")]
+    UnsupportedImageType,
+    #[error("invalid-image")]
+    InvalidImage,
+    #[error("invalid-aspect-ratio")]
+    InvalidAspectRatio,
+}
+
+pub struct ProcessedImage {
+    pub width: u32,
+    pub data: Vec<u8>,
+}
+
+// Detects the type from the magic bytes, the content type sent by the client is never trusted.
+// Only the pixels survive decoding, so EXIF and other metadata are dropped
+pub fn decode(bytes: &[u8]) -> Result<DynamicImage, ImageProcessingError> {
+    let format = image::guess_format(bytes)
+        .map_err(|_| ImageProcessingError::UnsupportedImageType)?;
+
+    if !matches!(format, ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP | ImageFormat::Gif) {
+        return Err(ImageProcessingError::UnsupportedImageType);
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+
+    let mut decoder = reader.into_decoder()
+        .map_err(|_| ImageProcessingError::InvalidImage)?;
+
+    // Camera pictures are often stored sideways with an orientation tag
+    let orientation = decoder.orientation()
+        .map_err(|_| ImageProcessingError::InvalidImage)?;
+
+    let mut image = DynamicImage::from_decoder(decoder)
+        .map_err(|_| ImageProcessingError::InvalidImage)?;
+
+    image.apply_orientation(orientation);
+
+    Ok(image)
+}
+
+// Crops to the center square and resizes to each of the given sizes
+pub fn square_variants(image: &DynamicImage, sizes: &[u32]) -> Result<Vec<ProcessedImage>, ImageProcessingError> {
+    sizes.iter()
+        .map(|size| encode(image.resize_to_fill(*size, *size, FilterType::Lanczos3)))
+        .collect()
+}
+
+// Rejects images narrower than min_ratio (width / height), then crops to the target ratio
+// and resizes to each of the given widths (largest first). Images are never upscaled past their own width
+pub fn wide_variants(image: &DynamicImage, widths: &[u32], min_ratio: f64, target_ratio: f64) -> Result<Vec<ProcessedImage>, ImageProcessingError> {
+    let ratio = image.width() as f64 / image.height() as f64;
+
+    if ratio < min_ratio {
+        return Err(ImageProcessingError::InvalidAspectRatio);
+    }
+
+    // Small images clamp several widths to the same one, which would only be encoded twice
+    let mut widths: Vec<u32> = widths.iter()
+        .map(|width| (*width).min(image.width()))
+        .collect();
+    widths.dedup();
+
+    widths.into_iter()
+        .map(|width| {
+            let height = ((width as f64 / target_ratio).round() as u32).max(1);
+
+            encode(image.resize_to_fill(width, height, FilterType::Lanczos3))
+        })
+        .collect()
+}
+
+fn encode(image: DynamicImage) -> Result<ProcessedImage, ImageProcessingError> {
+    let width = image.width();
+    let mut data = Vec::new();
+
+    DynamicImage::ImageRgba8(image.to_rgba8())
+        .write_to(&mut Cursor::new(&mut data), ImageFormat::WebP)
+        .map_err(|e| ImageProcessingError::UnexpectedError(e.into()))?;
+
+    Ok(ProcessedImage {
+        width,
+        data,
+    })
+}
@@ -1,7 +1,10 @@
-pub use connectors::GrpcAuthConnector;
+pub use connectors::{FileMailer, GrpcAuthConnector, InMemoryMailer, LocalMediaStorage, S3MediaStorage, SmtpMailer};
 
 pub mod session;
 pub mod secure_hasher;
+pub mod secret_cipher;
+pub mod totp;
+pub mod image_processing;
 pub mod logging;
 pub mod http;
 mod connectors;
@@ -0,0 +1,46 @@
+use aes_gcm::{AeadCore, Aes256Gcm, Key, KeyInit, Nonce};
+use aes_gcm::aead::Aead;
+use rand_core::OsRng;
+use sha2::{Digest, Sha256};
+
+const NONCE_LENGTH: usize = 12;
+
+// Encrypts secrets at rest with AES-256-GCM, the output is the nonce followed by the ciphertext
+#[derive(Clone)]
+pub struct SecretCipher {
+    cipher: Aes256Gcm,
+}
+
+impl SecretCipher {
+    // The key is derived from the configured passphrase, so any length can be used
+    pub fn new(passphrase: &str) -> Self {
+        let key = Sha256::digest(passphrase.as_bytes());
+
+        Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+        let ciphertext = self.cipher.encrypt(&nonce, plaintext)
+            .map_err(|e| anyhow::Error::msg(e.to_string()))?;
+
+        let mut output = nonce.to_vec();
+        output.extend(ciphertext);
+
+        Ok(output)
+    }
+
+    pub fn decrypt(&self, encrypted: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+        if encrypted.len() < NONCE_LENGTH {
+            return Err(anyhow::Error::msg("Encrypted secret is too short"));
+        }
+
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_LENGTH);
+
+        self.cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|e| anyhow::Error::msg(e.to_string()))
+    }
+}
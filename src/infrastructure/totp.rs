@@ -0,0 +1,119 @@
+use hmac::{Hmac, Mac};
+use rand_core::{OsRng, RngCore};
+use sha1::Sha1;
+use time::OffsetDateTime;
+
+// RFC 6238 with the defaults authenticator apps expect (HMAC-SHA1, 6 digits, 30 second steps)
+const STEP_SECONDS: i64 = 30;
+const DIGITS: u32 = 6;
+const SECRET_LENGTH: usize = 20;
+const ISSUER: &str = "Figure";
+
+pub fn generate_secret() -> Vec<u8> {
+    let mut secret = vec![0u8; SECRET_LENGTH];
+    OsRng.fill_bytes(&mut secret);
+    secret
+}
+
+pub fn encode_secret(secret: &[u8]) -> String {
+    base32::encode(base32::Alphabet::RFC4648 { padding: false }, secret)
+}
+
+pub fn provisioning_uri(secret: &[u8], account: &str) -> String {
+    let label: String = url::form_urlencoded::byte_serialize(format!("{ISSUER}:{account}").as_bytes()).collect();
+
+    format!("otpauth://totp/{label}?secret={}&issuer={ISSUER}&algorithm=SHA1&digits={DIGITS}&period={STEP_SECONDS}",
+            encode_secret(secret))
+}
+
+pub fn current_step(datetime: OffsetDateTime) -> i64 {
+    datetime.unix_timestamp() / STEP_SECONDS
+}
+
+// Returns the time step the code belongs to, allowing one step of clock drift in both directions
+pub fn verify(secret: &[u8], code: &str, datetime: OffsetDateTime) -> Option<i64> {
+    if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let code: u32 = code.parse().ok()?;
+    let step = current_step(datetime);
+
+    (step - 1..=step + 1)
+        .find(|step| code_for_step(secret, *step) == code)
+}
+
+// Code an authenticator app would show at the given time
+#[cfg(test)]
+pub fn code_at(secret: &[u8], datetime: OffsetDateTime) -> String {
+    format!("{:0width$}", code_for_step(secret, current_step(datetime)), width = DIGITS as usize)
+}
+
+fn code_for_step(secret: &[u8], step: i64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret)
+        .expect("HMAC accepts keys of any length");
+    mac.update(&(step as u64).to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // Dynamic truncation (RFC 4226, section 5.3)
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    binary % 10u32.pow(DIGITS)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    // Appendix B of RFC 6238, the SHA-1 seed and the last 6 of the 8 digit codes
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+    const RFC_VECTORS: [(i64, &str); 6] = [
+        (59, "287082"),
+        (1111111109, "081804"),
+        (1111111111, "050471"),
+        (1234567890, "005924"),
+        (2000000000, "279037"),
+        (20000000000, "353130"),
+    ];
+
+    fn datetime(timestamp: i64) -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(timestamp).unwrap()
+    }
+
+    #[test]
+    fn generates_rfc_6238_test_vectors() {
+        for (timestamp, code) in RFC_VECTORS {
+            assert_eq!(code_at(RFC_SECRET, datetime(timestamp)), code, "at {timestamp}");
+        }
+    }
+
+    #[test]
+    fn verifies_rfc_6238_test_vectors() {
+        for (timestamp, code) in RFC_VECTORS {
+            assert_eq!(verify(RFC_SECRET, code, datetime(timestamp)), Some(timestamp / STEP_SECONDS), "at {timestamp}");
+        }
+    }
+
+    #[test]
+    fn allows_one_step_of_clock_drift() {
+        assert_eq!(verify(RFC_SECRET, "287082", datetime(59 + STEP_SECONDS)), Some(1));
+        assert_eq!(verify(RFC_SECRET, "287082", datetime(59 - STEP_SECONDS)), Some(1));
+        assert_eq!(verify(RFC_SECRET, "287082", datetime(59 + 2 * STEP_SECONDS)), None);
+    }
+
+    #[test]
+    fn rejects_malformed_codes() {
+        assert_eq!(verify(RFC_SECRET, "28708", datetime(59)), None);
+        assert_eq!(verify(RFC_SECRET, "2870821", datetime(59)), None);
+        assert_eq!(verify(RFC_SECRET, "28708a", datetime(59)), None);
+        assert_eq!(verify(RFC_SECRET, "+28708", datetime(59)), None);
+    }
+}